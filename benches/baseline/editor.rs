@@ -0,0 +1,94 @@
+//! `GapBuffer` as it was before the line index, kept to benchmark the new one against.
+
+use log::{error, info};
+pub struct GapBuffer {
+    pub buffer: Vec<char>,
+    pub capacity: usize,
+    pub gap_start: usize,
+    pub gap_end: usize,
+}
+
+impl GapBuffer {
+    pub fn from_str(text: &str, capacity: usize) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let length = chars.len();
+        let mut buffer: Vec<char> = Vec::with_capacity(capacity + length);
+        buffer.extend_from_slice(&chars);
+        buffer.resize(capacity + length, '\0');
+        GapBuffer {
+            buffer,
+            capacity,
+            gap_start: length,
+            gap_end: length + capacity - 1,
+        }
+    }
+    fn move_gap_left(&mut self, index: usize) {
+        while self.gap_start > index {
+            self.gap_start -= 1;
+            self.gap_end -= 1;
+            self.buffer[self.gap_end + 1] = self.buffer[self.gap_start];
+            self.buffer[self.gap_start] = '\0';
+        }
+    }
+
+    fn move_gap_right(&mut self, index: usize) {
+        while self.gap_start < index {
+            self.gap_start += 1;
+            self.gap_end += 1;
+            self.buffer[self.gap_start - 1] = self.buffer[self.gap_end];
+            self.buffer[self.gap_end] = '\0';
+        }
+    }
+
+    fn grow(&mut self) {
+        let new_capacity = self.capacity * 2;
+        let new_size = new_capacity + self.buffer.len();
+        let mut new_buffer = Vec::with_capacity(new_size);
+        new_buffer.extend_from_slice(&self.buffer[..self.gap_start]);
+        for _ in 0..new_capacity {
+            new_buffer.push('\0');
+        }
+        let gap_end = new_buffer.len();
+        new_buffer.extend_from_slice(&self.buffer[self.gap_end + 1..]);
+        self.gap_end = gap_end;
+        self.buffer = new_buffer;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let gap_range = self.gap_end - self.gap_start;
+        if gap_range == 1 {
+            self.grow();
+        }
+        self.buffer[self.gap_start] = c;
+        self.gap_start += 1;
+    }
+
+    pub fn delete_char(&mut self) {
+        if self.gap_start == 0 {
+            return;
+        }
+        self.gap_start -= 1;
+        self.buffer[self.gap_start] = '\0';
+    }
+
+    pub fn move_gap(&mut self, index: usize) {
+        let gap_size = self.gap_end - self.gap_start;
+        if index + gap_size > self.buffer.len() {
+            error!("Gap will overflow the buffer if moved to this index.");
+            return;
+        }
+        if index == self.gap_start {
+            info!("Gap is already positioned on this index.");
+            return;
+        }
+        if index < self.gap_start {
+            self.move_gap_left(index);
+        }
+        if index > self.gap_start {
+            self.move_gap_right(index);
+        }
+    }
+    pub fn to_string(&self) -> String {
+        self.buffer.iter().filter(|&&c| c != '\0').collect()
+    }
+}
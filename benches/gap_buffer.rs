@@ -0,0 +1,186 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use ratatui::text::{Line, Span};
+use syntect::{
+    easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet, util::LinesWithEndings,
+};
+use syntect_tui::into_span;
+
+// dial is a binary crate, the buffer is compiled into the benchmark directly.
+#[allow(dead_code, unused_imports)]
+#[path = "../src/editor.rs"]
+mod editor;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/highlight.rs"]
+mod highlight;
+// the buffer before the line index, which the editor used to scan on every keystroke.
+#[allow(dead_code, clippy::all)]
+#[path = "baseline/editor.rs"]
+mod baseline;
+
+use editor::GapBuffer;
+use highlight::HighlightCache;
+
+const LINE: &str = "    let total = items.iter().map(|item| item.price * 2).sum::<u32>(); // sum\n";
+const THEME: &str = "base16-eighties.dark";
+// rows of the editor drawn on every frame.
+const VIEWPORT: usize = 40;
+
+fn text(lines: usize) -> String {
+    LINE.repeat(lines)
+}
+
+/// Line and column of the cursor the way the editor found them before the line index, by
+/// scanning the text before the gap.
+fn baseline_cursor(buffer: &baseline::GapBuffer) -> (usize, usize) {
+    let before = &buffer.buffer[..buffer.gap_start];
+    let line = before.iter().filter(|&&c| c == '\n').count();
+    let line_start = before.iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1);
+    (line, buffer.gap_start - line_start)
+}
+
+fn cursor(buffer: &GapBuffer) -> (usize, usize) {
+    let cursor = buffer.cursor();
+    (buffer.line_of(cursor), cursor - buffer.line_start(cursor))
+}
+
+fn line_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cursor position");
+    for lines in [100, 10_000, 100_000] {
+        let text = text(lines);
+        let index = text.chars().count() - LINE.len() / 2;
+        let mut old = baseline::GapBuffer::from_str(&text, 1024);
+        old.move_gap(index);
+        let mut new = GapBuffer::from_str(&text, 1024);
+        new.move_gap(index);
+        group.bench_with_input(BenchmarkId::new("baseline", lines), &old, |b, buffer| {
+            b.iter(|| baseline_cursor(black_box(buffer)))
+        });
+        group.bench_with_input(BenchmarkId::new("line index", lines), &new, |b, buffer| {
+            b.iter(|| cursor(black_box(buffer)))
+        });
+    }
+    group.finish();
+}
+
+fn editing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("editing");
+    for lines in [100, 10_000] {
+        let text = text(lines);
+        // typing keeps the index up to date, which is the cost paid for the lookups
+        group.bench_with_input(
+            BenchmarkId::new("type a line/baseline", lines),
+            &text,
+            |b, text| {
+                b.iter_batched(
+                    || {
+                        let mut buffer = baseline::GapBuffer::from_str(text, 1024);
+                        buffer.move_gap(buffer.gap_start / 2);
+                        buffer
+                    },
+                    |mut buffer| LINE.chars().for_each(|c| buffer.insert_char(c)),
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("type a line/line index", lines),
+            &text,
+            |b, text| {
+                b.iter_batched(
+                    || {
+                        let mut buffer = GapBuffer::from_str(text, 1024);
+                        buffer.move_gap(buffer.len() / 2);
+                        buffer
+                    },
+                    |mut buffer| buffer.insert_str(LINE),
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("move gap/baseline", lines),
+            &text,
+            |b, text| {
+                b.iter_batched(
+                    || baseline::GapBuffer::from_str(text, 1024),
+                    |mut buffer| buffer.move_gap(0),
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("move gap/line index", lines),
+            &text,
+            |b, text| {
+                b.iter_batched(
+                    || GapBuffer::from_str(text, 1024),
+                    |mut buffer| buffer.move_gap(0),
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+/// A typed character from the key event to the drawn frame, as the editor handles it.
+fn keystroke(c: &mut Criterion) {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let (syntax, theme) = (
+        syntax_set.find_syntax_by_extension("rs").unwrap(),
+        &theme_set.themes[THEME],
+    );
+    let mut group = c.benchmark_group("keystroke");
+    // the baseline highlights the whole snippet on every frame
+    group.sample_size(10);
+    for lines in [1_000, 10_000] {
+        let text = text(lines);
+        let index = text.chars().count() / 2;
+
+        // the cursor is found by scanning, then the text is rebuilt and highlighted in full
+        let mut buffer = baseline::GapBuffer::from_str(&text, 1024);
+        buffer.move_gap(index);
+        group.bench_function(BenchmarkId::new("baseline", lines), |b| {
+            b.iter(|| {
+                buffer.insert_char('x');
+                black_box(baseline_cursor(&buffer));
+                let text = buffer.to_string();
+                let mut highlighter = HighlightLines::new(syntax, theme);
+                let frame: Vec<Line> = LinesWithEndings::from(&text)
+                    .map(|line| {
+                        let spans: Vec<Span> = highlighter
+                            .highlight_line(line, &syntax_set)
+                            .unwrap()
+                            .into_iter()
+                            .filter_map(|segment| into_span(segment).ok())
+                            .collect();
+                        Line::from(spans)
+                    })
+                    .collect();
+                black_box(frame);
+            })
+        });
+
+        // the cursor comes from the index, and the viewport is highlighted from the edited line
+        let mut buffer = GapBuffer::from_str(&text, 1024);
+        buffer.move_gap(index);
+        let mut cache = HighlightCache::default();
+        group.bench_function(BenchmarkId::new("line index", lines), |b| {
+            b.iter(|| {
+                buffer.insert_char('x');
+                let (line, _) = black_box(cursor(&buffer));
+                if let Some(index) = buffer.take_edited_from() {
+                    cache.invalidate_from(buffer.line_of(index));
+                }
+                let visible = line.saturating_sub(VIEWPORT / 2)..line + VIEWPORT / 2;
+                let frame = cache.highlight(&buffer, visible, &syntax_set, syntax, (THEME, theme));
+                black_box(frame);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, line_lookup, editing, keystroke);
+criterion_main!(benches);
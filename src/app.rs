@@ -1,16 +1,41 @@
+use crate::clipboard::Clipboard;
+use crate::config::{self, Config, LayoutConfig, Paths};
+use crate::events::{AppEvent, Events};
+use crate::external;
+use crate::keymap::{Action, KeyChord, Keymap, PendingKeys, Resolution};
 use crate::persistence::{load_snippets, save_snippets};
-use crate::view::{Component, ViewManager};
+use crate::readline::KillRing;
+use crate::search::SearchIndex;
+use crate::theme::UiTheme;
+use crate::view::{Component, ViewManager, centered};
 use anyhow::{Context, Result};
-use log::error;
-use ratatui::crossterm::style::Color;
+use log::{error, info};
 use ratatui::style::Stylize;
+use ratatui::text::Span;
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, Borders, Widget},
+    crossterm::event::{Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Position, Rect},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
 use serde::{Deserialize, Serialize};
+use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
+
+use std::{
+    collections::HashMap,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+// smallest terminal the layout is drawn in.
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 15;
+// interval of the ticks expiring the timers.
+const TICK_RATE: Duration = Duration::from_millis(250);
+// how long a status message stays on screen.
+const STATUS_DURATION: Duration = Duration::from_secs(3);
+// time without edits after which the modified snippets are saved.
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Snippet {
@@ -19,67 +44,177 @@ pub struct Snippet {
     pub title: String,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum AppMode {
     Select,
     Search,
     Edit,
     Command,
     Popup,
+    ThemePicker,
+}
+
+impl AppMode {
+    /// Name of the mode's table in the `[keys]` section of the config file.
+    pub fn config_name(self) -> String {
+        match self {
+            AppMode::Select => String::from("select"),
+            AppMode::Search => String::from("search"),
+            AppMode::Edit => String::from("edit"),
+            AppMode::Command => String::from("command"),
+            AppMode::Popup => String::from("popup"),
+            AppMode::ThemePicker => String::from("theme_picker"),
+        }
+    }
 }
 
 pub struct AppState {
-    pub snippet_list: Vec<Snippet>,
+    snippet_list: Vec<Snippet>,
     pub selected_index: usize,
     pub mode: AppMode,
     pub should_exit: bool,
     pub current_area: Rect,
     pub focused_editor: bool,
     pub focused_search: bool,
-    pub search_query: String,
+    search_query: String,
+    search_index: SearchIndex,
+    // indices in snippet_list of the snippets matching search_query.
+    filtered: Vec<usize>,
+    pub theme: String,
+    pub ui_theme: UiTheme,
+    pub keymap: Keymap,
+    pub kill_ring: KillRing,
+    pub clipboard: Clipboard,
+    // message shown at the bottom of the screen, and when it expires.
+    status: Option<(String, Instant)>,
+    // snippets with unsaved changes by index in snippet_list, with the count of edits at
+    // their last change so that a save only clears the changes it wrote.
+    modified: HashMap<usize, usize>,
+    edits: usize,
+    last_edit: Option<Instant>,
 }
 
 impl AppState {
-    pub fn get_content(&self) -> Option<String> {
-        if let Some(actual_index) = self.get_selected_snippet_index() {
-            self.snippet_list
-                .get(actual_index)
-                .map(|snippet| snippet.code.clone())
-        } else {
-            None
+    pub fn new(
+        snippet_list: Vec<Snippet>,
+        theme: String,
+        ui_theme: UiTheme,
+        keymap: Keymap,
+    ) -> Self {
+        let search_index = SearchIndex::new(snippet_list.iter().map(|snippet| &snippet.title[..]));
+        let mut state = AppState {
+            snippet_list,
+            search_query: String::new(),
+            search_index,
+            filtered: Vec::new(),
+            selected_index: 0,
+            mode: AppMode::Command,
+            should_exit: false,
+            current_area: Rect::default(),
+            focused_editor: false,
+            focused_search: false,
+            theme,
+            ui_theme,
+            keymap,
+            kill_ring: KillRing::default(),
+            clipboard: Clipboard::default(),
+            status: None,
+            modified: HashMap::new(),
+            edits: 0,
+            last_edit: None,
+        };
+        state.refilter();
+        state
+    }
+
+    pub fn set_search_query(&mut self, query: String) {
+        self.search_query = query;
+        self.refilter();
+    }
+
+    pub fn add_snippet(&mut self, snippet: Snippet) {
+        self.search_index.push(&snippet.title);
+        self.snippet_list.push(snippet);
+        self.mark_modified(self.snippet_list.len() - 1);
+        self.refilter();
+    }
+
+    /// Replaces the code of the snippet at `index` in the full list.
+    pub fn set_code(&mut self, index: usize, code: String) {
+        if let Some(snippet) = self.snippet_list.get_mut(index)
+            && snippet.code != code
+        {
+            snippet.code = code;
+            self.mark_modified(index);
         }
     }
 
-    pub fn filtered_snippets(&self) -> Vec<(usize, &Snippet)> {
-        let filtered_list: Vec<(usize, &Snippet)> = self
-            .snippet_list
-            .iter()
-            .enumerate()
-            .filter(|(_, snippet)| {
-                snippet
-                    .title
-                    .to_lowercase()
-                    .contains(&self.search_query.to_lowercase())
-            })
-            .collect();
+    /// Copies the text of the open buffer of the snippet at `index`, whose edits were already
+    /// marked.
+    pub fn copy_code(&mut self, index: usize, code: String) {
+        if let Some(snippet) = self.snippet_list.get_mut(index) {
+            snippet.code = code;
+        }
+    }
+
+    /// Records an edit of the snippet at `index` in the full list, to be saved.
+    pub fn mark_modified(&mut self, index: usize) {
+        self.edits += 1;
+        self.modified.insert(index, self.edits);
+        self.last_edit = Some(Instant::now());
+    }
+
+    /// Clears the changes made up to the edit `edits`, once they were written.
+    fn mark_saved(&mut self, edits: usize) {
+        self.modified.retain(|_, edit| *edit > edits);
+    }
 
-        if filtered_list.len() == 0 {
-            return self.snippet_list.iter().enumerate().collect();
+    /// Whether the snippet at `index` in the full list has unsaved changes.
+    pub fn is_modified(&self, index: usize) -> bool {
+        self.modified.contains_key(&index)
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        !self.modified.is_empty()
+    }
+
+    fn refilter(&mut self) {
+        self.filtered = self.search_index.search(&self.search_query);
+        if self.filtered.is_empty() {
+            self.filtered = (0..self.snippet_list.len()).collect();
         }
-        filtered_list
+    }
+
+    /// Indices in the full list of the snippets matching the search, or of every snippet when
+    /// none match.
+    pub fn filtered_indices(&self) -> &[usize] {
+        &self.filtered
+    }
+
+    /// Snippets at the positions `range` of the filtered list.
+    pub fn filtered_snippets(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (usize, &Snippet)> {
+        let end = range.end.min(self.filtered.len());
+        let start = range.start.min(end);
+        self.filtered[start..end]
+            .iter()
+            .map(|&index| (index, &self.snippet_list[index]))
     }
 
     pub fn get_selected_snippet_index(&self) -> Option<usize> {
-        self.filtered_snippets()
-            .get(self.selected_index)
-            .map(|(i, _)| *i)
+        self.filtered.get(self.selected_index).copied()
+    }
+
+    pub fn snippet(&self, index: usize) -> Option<&Snippet> {
+        self.snippet_list.get(index)
     }
 
-    pub fn get_current_snippet(&self) -> Option<&Snippet> {
-        if let Some(actual_index) = self.get_selected_snippet_index() {
-            self.snippet_list.get(actual_index)
-        } else {
-            None
+    /// Selects the snippet at `index` in the full list if the search didn't filter it out.
+    pub fn select_snippet(&mut self, index: usize) {
+        if let Some(position) = self.filtered.iter().position(|&i| i == index) {
+            self.selected_index = position;
         }
     }
 
@@ -103,167 +238,689 @@ impl AppState {
         self.blur_search();
         self.blur_editor();
     }
+
+    /// Shows `message` at the bottom of the screen for a few seconds.
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now() + STATUS_DURATION));
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_ref().map(|(message, _)| message.as_str())
+    }
+
+    /// Clears the status message once it expired, returns whether it did.
+    fn expire_status(&mut self, now: Instant) -> bool {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|(_, expires)| *expires <= now)
+        {
+            self.status = None;
+            return true;
+        }
+        false
+    }
 }
 
 pub struct App {
     pub app_state: AppState,
     pub view_manager: ViewManager,
+    paths: Paths,
+    layout: LayoutConfig,
+    // keys of a binding that is still being typed.
+    pending_keys: PendingKeys,
+    // areas of the panes in the last frame, used to find what the mouse points at.
+    search_area: Rect,
+    list_area: Rect,
+    editor_area: Rect,
+    // whether the divider between the list and the editor is being dragged.
+    resizing: bool,
+    events: Events,
+    // a save runs in the background, at most one at a time.
+    saving: bool,
+    save_queued: bool,
+    // count of edits written by the running save, and when the last save started.
+    saving_edits: usize,
+    last_save: Option<Instant>,
+    // whether quitting with unsaved changes waits for a confirmation.
+    confirming_quit: bool,
+    // whether the app quits once the save it is waiting for succeeded.
+    quit_after_save: bool,
+    // snippet to open in the external editor, which needs the terminal the loop holds.
+    external_edit: Option<usize>,
 }
 
 impl App {
-    pub fn new() -> Self {
-        let snippet_list = load_snippets().expect("snippet_list should not be empty");
-        let app_state = AppState {
-            snippet_list: snippet_list.clone(),
-            search_query: String::new(),
-            selected_index: 0,
-            mode: AppMode::Command,
-            should_exit: false,
-            current_area: Rect::default(),
-            focused_editor: false,
-            focused_search: false,
-        };
+    pub fn new(
+        config: Config,
+        paths: Paths,
+        syntax_set: SyntaxSet,
+        theme_set: ThemeSet,
+        ui_theme: UiTheme,
+    ) -> Self {
+        let events = Events::new(TICK_RATE);
+        App::with_events(config, paths, syntax_set, theme_set, ui_theme, events)
+    }
+
+    fn with_events(
+        config: Config,
+        paths: Paths,
+        syntax_set: SyntaxSet,
+        theme_set: ThemeSet,
+        ui_theme: UiTheme,
+        events: Events,
+    ) -> Self {
+        let snippet_list =
+            load_snippets(&paths.data_dir).expect("snippet_list should not be empty");
+        let keymap = Keymap::new(&config.keys).expect("keymap should be validated with the config");
+        let app_state = AppState::new(snippet_list, config.editor.theme.clone(), ui_theme, keymap);
 
         App {
             app_state,
-            view_manager: ViewManager::new(),
+            view_manager: ViewManager::new(&config.editor, syntax_set, theme_set),
+            paths,
+            layout: config.layout,
+            pending_keys: PendingKeys::default(),
+            search_area: Rect::default(),
+            list_area: Rect::default(),
+            editor_area: Rect::default(),
+            resizing: false,
+            events,
+            saving: false,
+            save_queued: false,
+            saving_edits: 0,
+            last_save: None,
+            confirming_quit: false,
+            quit_after_save: false,
+            external_edit: None,
+        }
+    }
+
+    /// Writes a copy of the snippets from another thread, after the save already running.
+    fn save(&mut self) {
+        if self.saving {
+            self.save_queued = true;
+            return;
         }
+        self.view_manager
+            .editor_component
+            .sync_buffer_to_state(&mut self.app_state);
+        self.saving = true;
+        self.saving_edits = self.app_state.edits;
+        self.last_save = Some(Instant::now());
+        let data_dir = self.paths.data_dir.clone();
+        let snippets = self.app_state.snippet_list.clone();
+        self.events
+            .spawn(move || AppEvent::Saved(save_snippets(&data_dir, &snippets)));
     }
 
-    fn switch_mode(&mut self, event: &Event) {
+    fn finish_save(&mut self, result: Result<()>) {
+        self.saving = false;
+        match result {
+            Ok(()) => self.app_state.mark_saved(self.saving_edits),
+            Err(err) => {
+                error!("{:#}", err);
+                // the app stays open rather than losing the edits
+                self.quit_after_save = false;
+                self.app_state
+                    .set_status(format!(" Could not save snippets: {:#} ", err));
+            }
+        }
+        if self.save_queued {
+            self.save_queued = false;
+            self.save();
+        }
+        if self.quit_after_save && !self.saving {
+            self.app_state.should_exit = true;
+        }
+    }
+
+    /// Handles an event of the loop, returns whether the screen needs to be redrawn.
+    fn handle_app_event(&mut self, event: AppEvent) -> Result<bool> {
         match event {
-            Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => {
-                            self.app_state.should_exit = true;
-                        }
-                        KeyCode::Char('e') => {
-                            self.app_state.mode = AppMode::Edit;
-                        }
-                        KeyCode::Char('s') => {
-                            self.app_state.mode = AppMode::Select;
-                        }
-                        KeyCode::Char('/') => self.app_state.mode = AppMode::Search,
-                        _ => {}
-                    }
+            AppEvent::Input(event) => {
+                self.handle_event(&event)?;
+                // the list shows the edited snippets as modified, their text is only copied
+                // once it is saved or read
+                self.view_manager
+                    .editor_component
+                    .track_edits(&mut self.app_state);
+                Ok(changes_state(&event))
+            }
+            AppEvent::Tick => {
+                let now = Instant::now();
+                let idle = |since: Option<Instant>| {
+                    since.is_none_or(|since| now.duration_since(since) >= AUTOSAVE_DELAY)
+                };
+                if self.app_state.has_unsaved_changes()
+                    && !self.saving
+                    && idle(self.app_state.last_edit)
+                    && idle(self.last_save)
+                {
+                    self.save();
+                }
+                Ok(self.app_state.expire_status(now))
+            }
+            AppEvent::Saved(result) => {
+                self.finish_save(result);
+                Ok(true)
+            }
+        }
+    }
+
+    fn switch_mode(&mut self, action: Action) {
+        match action {
+            Action::Quit if self.app_state.has_unsaved_changes() => self.confirming_quit = true,
+            Action::Quit => self.app_state.should_exit = true,
+            Action::EditMode => self.app_state.mode = AppMode::Edit,
+            Action::SelectMode => self.app_state.mode = AppMode::Select,
+            Action::SearchMode => self.app_state.mode = AppMode::Search,
+            Action::OpenThemePicker => self.app_state.mode = AppMode::ThemePicker,
+            _ => {}
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) -> Result<()> {
+        if self.confirming_quit {
+            self.confirm_quit(event);
+            return Ok(());
+        }
+        if let Event::Key(key) = event
+            && key.kind == KeyEventKind::Press
+        {
+            // with vim mode on, the editor only leaves keys bound to back, to the tabs and panes,
+            // to the external editor, to the find bar and to soft wrap to the keymap
+            let editor = &mut self.view_manager.editor_component;
+            if self.app_state.mode == AppMode::Edit
+                && editor.vim_mode().is_some()
+                && !editor.is_finding()
+            {
+                let bypasses_vim = matches!(
+                    self.app_state
+                        .keymap
+                        .resolve(&AppMode::Edit, &[KeyChord::from(key)]),
+                    Resolution::Action(action) if action == Action::Back
+                        || action == Action::OpenExternalEditor
+                        || action == Action::Find
+                        || action == Action::ToggleWrap
+                        || action.is_layout()
+                );
+                if editor.captures_keys() || !bypasses_vim {
+                    editor.handle_vim_key(key, &mut self.app_state);
+                    return Ok(());
+                }
+            }
+            let (typed, resolution) =
+                self.pending_keys
+                    .push(&self.app_state.keymap, &self.app_state.mode, *key);
+            // unbound keys are only forwarded as text input
+            if let Some(component) = self.view_manager.active_component(self.app_state.mode) {
+                for key in typed {
+                    component.handle_event(&Event::Key(key), &mut self.app_state);
                 }
             }
+            if let Resolution::Action(action) = resolution {
+                self.handle_action(action)?;
+            }
+        } else if let Event::Paste(_) = event
+            && let Some(component) = self.view_manager.active_component(self.app_state.mode)
+        {
+            component.handle_event(event, &mut self.app_state);
+        } else if let Event::Mouse(mouse) = event {
+            self.handle_mouse(event, mouse)?;
+        } else if let Event::Resize(width, height) = event {
+            // the next frame is laid out for the new size, cursors included
+            info!("terminal resized to {}x{}", width, height);
+        }
+        Ok(())
+    }
+
+    /// Answers the quit confirmation: `y` saves and quits, `n` quits and any other key cancels.
+    fn confirm_quit(&mut self, event: &Event) {
+        let Event::Key(key) = event else {
+            return;
+        };
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        self.confirming_quit = false;
+        match key.code {
+            // the app quits once the save succeeded
+            KeyCode::Char('y') => {
+                self.quit_after_save = true;
+                self.save();
+            }
+            KeyCode::Char('n') => self.app_state.should_exit = true,
             _ => {}
         }
     }
 
+    fn handle_mouse(&mut self, event: &Event, mouse: &MouseEvent) -> Result<()> {
+        match self.app_state.mode {
+            AppMode::Popup => {
+                self.view_manager
+                    .add_snippet_popup_component
+                    .handle_event(event, &mut self.app_state);
+                return Ok(());
+            }
+            AppMode::ThemePicker => return Ok(()),
+            _ => {}
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let clicked = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+        // the borders of the list and of the editor form the divider
+        let on_divider = (mouse.column + 1 == self.list_area.right()
+            || mouse.column == self.editor_area.x)
+            && (self.search_area.y..self.list_area.bottom()).contains(&mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if on_divider => {
+                self.resizing = true;
+                return Ok(());
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.resizing => {
+                self.resize_list(mouse.column);
+                return Ok(());
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.resizing => {
+                self.resizing = false;
+                self.resize_list(mouse.column);
+                return Ok(());
+            }
+            _ => {}
+        }
+        if self.editor_area.contains(position) {
+            if clicked {
+                self.focus(AppMode::Edit)?;
+            }
+            self.view_manager
+                .editor_component
+                .handle_event(event, &mut self.app_state);
+        } else if self.list_area.contains(position) {
+            if clicked {
+                self.focus(AppMode::Select)?;
+            }
+            self.view_manager
+                .snippet_list_component
+                .handle_event(event, &mut self.app_state);
+        } else if self.search_area.contains(position) && clicked {
+            self.focus(AppMode::Search)?;
+            self.view_manager
+                .search_component
+                .handle_event(event, &mut self.app_state);
+        }
+        Ok(())
+    }
+
+    /// Leaves the current mode like `Back` would, and enters `mode`.
+    fn focus(&mut self, mode: AppMode) -> Result<()> {
+        if self.app_state.mode != mode {
+            self.handle_action(Action::Back)?;
+            self.app_state.mode = mode;
+        }
+        Ok(())
+    }
+
+    /// Moves the divider between the list and the editor to `column`.
+    fn resize_list(&mut self, column: u16) {
+        let left = self.search_area.x;
+        let width = self.editor_area.right().saturating_sub(left).max(1);
+        let percentage = column.saturating_sub(left) as u32 * 100 / width as u32;
+        self.layout.list_width = (percentage as u16).clamp(10, 90);
+    }
+
+    fn handle_action(&mut self, action: Action) -> Result<()> {
+        if action == Action::OpenExternalEditor {
+            self.external_edit = match self.app_state.mode {
+                AppMode::Edit => self.view_manager.editor_component.focused_snippet(),
+                _ => self.app_state.get_selected_snippet_index(),
+            };
+            return Ok(());
+        }
+        if action == Action::Back {
+            // the find bar closes before the editor is left
+            if self.app_state.mode == AppMode::Edit
+                && self.view_manager.editor_component.close_find()
+            {
+                return Ok(());
+            }
+            // on command mode, unfocus
+            if self.app_state.mode == AppMode::ThemePicker {
+                self.view_manager
+                    .theme_picker_component
+                    .cancel(&mut self.app_state);
+            }
+            self.app_state.mode = AppMode::Command;
+            self.app_state.blur();
+            return Ok(());
+        }
+        let mode = self.app_state.mode;
+        match self.view_manager.active_component(mode) {
+            Some(component) => component.handle_action(action, &mut self.app_state),
+            None => self.switch_mode(action),
+        }
+        // the picker closes once a theme is applied
+        if mode == AppMode::ThemePicker
+            && self.app_state.mode != AppMode::ThemePicker
+            && let Err(err) = config::save_theme(&self.paths.config_file, &self.app_state.theme)
+        {
+            error!("{:#}", err);
+            self.app_state.set_status(" Could not save the theme ");
+        }
+        Ok(())
+    }
+
     fn render_outer_block(&self, f: &mut Frame) -> Rect {
-        let mode_text = format!(" Mode: {:?} ", self.app_state.mode);
-        let help_text = " 󰈆 [q] Quit   │   [s] Select Mode   │  [e] Edit Mode  |   [/] Search ";
+        let mode_text = match self.view_manager.editor_component.vim_mode() {
+            Some(vim_mode) if self.app_state.mode == AppMode::Edit => {
+                format!(" Mode: {:?} ({}) ", self.app_state.mode, vim_mode)
+            }
+            _ => format!(" Mode: {:?} ", self.app_state.mode),
+        };
+        let keymap = &self.app_state.keymap;
+        let label = |action| keymap.label(&AppMode::Command, action);
+        let help_text = format!(
+            " 󰈆 [{}] Quit   │   [{}] Select Mode   │  [{}] Edit Mode  |   [{}] Search  |  [{}] Theme ",
+            label(Action::Quit),
+            label(Action::SelectMode),
+            label(Action::EditMode),
+            label(Action::SearchMode),
+            label(Action::OpenThemePicker),
+        );
+        let ui_theme = &self.app_state.ui_theme;
         let block = Block::new()
             .borders(Borders::ALL)
             .border_type(ratatui::widgets::BorderType::Rounded)
-            .title(" Dial ")
+            .border_style(ui_theme.border_style())
+            .title(Span::styled(" Dial ", ui_theme.title_style()))
             .bold()
             .title_alignment(ratatui::layout::Alignment::Center)
-            .title_bottom(mode_text.bg(Color::DarkBlue).black())
-            .title_bottom(help_text);
+            .title_bottom(Span::styled(mode_text, ui_theme.badge_style()));
+        // a status message takes the place of the key hints, which leave no room for it
+        let block = match self.app_state.status() {
+            Some(status) => block.title_bottom(Span::styled(status, ui_theme.badge_style())),
+            None => block.title_bottom(Span::styled(help_text, ui_theme.help_style())),
+        };
         let inner_area = block.inner(f.area());
         block.render(f.area(), f.buffer_mut());
         inner_area
     }
 
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), anyhow::Error> {
-        while self.app_state.should_exit != true {
-            terminal
-                .draw(|f: &mut Frame| {
-                    let inner_area = self.render_outer_block(f);
-                    let horizontal_chunks = Layout::new(
-                        Direction::Horizontal,
-                        vec![Constraint::Percentage(30), Constraint::Percentage(70)],
-                    )
-                    .split(inner_area);
-                    let vertical_chunks = Layout::new(
-                        Direction::Vertical,
-                        vec![Constraint::Percentage(10), Constraint::Percentage(90)],
-                    )
-                    .split(horizontal_chunks[0]);
-                    self.view_manager.search_component.render(
-                        vertical_chunks[0],
-                        f,
-                        &self.app_state,
-                    );
-                    self.view_manager.snippet_list_component.render(
-                        vertical_chunks[1],
+        let mut redraw = true;
+        while !self.app_state.should_exit {
+            if redraw {
+                self.draw(terminal)?;
+            }
+            let event = self.events.next()?;
+            redraw = self.handle_app_event(event)?;
+            if let Some(snippet) = self.external_edit.take() {
+                self.edit_externally(terminal, snippet)?;
+                redraw = true;
+            }
+        }
+        // the last changes are only written once the running save is done
+        while self.saving {
+            if let AppEvent::Saved(result) = self.events.next()? {
+                self.finish_save(result);
+            }
+        }
+        Ok(())
+    }
+
+    /// Suspends the interface while `snippet` is edited in $VISUAL or $EDITOR.
+    fn edit_externally(&mut self, terminal: &mut DefaultTerminal, snippet: usize) -> Result<()> {
+        self.view_manager
+            .editor_component
+            .sync_buffer_to_state(&mut self.app_state);
+        let Some(Snippet { code, language, .. }) = self.app_state.snippet(snippet).cloned() else {
+            return Ok(());
+        };
+        self.events.pause_input();
+        external::restore_terminal();
+        let result = external::editor_command()
+            .and_then(|command| external::edit(&command, &code, &language));
+        external::resume_terminal(terminal)?;
+        self.events.resume_input();
+        match result {
+            Ok(edited) => {
+                self.view_manager.editor_component.reload(snippet, &edited);
+                self.app_state.set_code(snippet, edited);
+            }
+            Err(err) => {
+                error!("{:#}", err);
+                self.app_state
+                    .set_status(" Could not open the external editor ");
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        terminal
+            .draw(|f: &mut Frame| {
+                if f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT {
+                    render_too_small(f);
+                    return;
+                }
+                let inner_area = self.render_outer_block(f);
+                let horizontal_chunks = Layout::new(
+                    Direction::Horizontal,
+                    vec![
+                        Constraint::Percentage(self.layout.list_width),
+                        Constraint::Percentage(100 - self.layout.list_width),
+                    ],
+                )
+                .split(inner_area);
+                let vertical_chunks = Layout::new(
+                    Direction::Vertical,
+                    vec![
+                        Constraint::Percentage(self.layout.search_height),
+                        Constraint::Percentage(100 - self.layout.search_height),
+                    ],
+                )
+                .split(horizontal_chunks[0]);
+                self.view_manager
+                    .search_component
+                    .render(vertical_chunks[0], f, &self.app_state);
+                self.view_manager.snippet_list_component.render(
+                    vertical_chunks[1],
+                    f,
+                    &self.app_state,
+                );
+                self.view_manager
+                    .editor_component
+                    .render(horizontal_chunks[1], f, &self.app_state);
+                // only render popup in popup mode
+                if self.app_state.mode == AppMode::Popup {
+                    self.view_manager.add_snippet_popup_component.render(
+                        f.area(),
                         f,
                         &self.app_state,
-                    );
-                    self.view_manager.editor_component.render(
-                        horizontal_chunks[1],
+                    )
+                }
+                if self.app_state.mode == AppMode::ThemePicker {
+                    self.view_manager.theme_picker_component.render(
+                        horizontal_chunks[0],
                         f,
                         &self.app_state,
-                    );
-                    // only render popup in popup mode
-                    if self.app_state.mode == AppMode::Popup {
-                        self.view_manager.add_snippet_popup_component.render(
-                            f.area(),
-                            f,
-                            &self.app_state,
-                        )
+                    )
+                }
+                if self.confirming_quit {
+                    render_confirm_quit(f, &self.app_state.ui_theme);
+                }
+                self.search_area = vertical_chunks[0];
+                self.list_area = vertical_chunks[1];
+                self.editor_area = horizontal_chunks[1];
+                // update current area
+                match self.app_state.mode {
+                    AppMode::Select => {
+                        self.app_state.current_area = horizontal_chunks[0];
+                    }
+                    AppMode::Edit => {
+                        self.app_state.current_area = horizontal_chunks[1];
                     }
-                    // update current area
-                    match self.app_state.mode {
-                        AppMode::Select => {
-                            self.app_state.current_area = horizontal_chunks[0];
-                        }
-                        AppMode::Edit => {
-                            self.app_state.current_area = horizontal_chunks[1];
-                        }
-                        AppMode::Search => {
-                            self.app_state.current_area = vertical_chunks[0];
-                        }
-                        _ => {}
+                    AppMode::Search => {
+                        self.app_state.current_area = vertical_chunks[0];
                     }
-                })
-                .with_context(|| "could not draw frame")?;
-            let result = event::read();
-            match result {
-                Ok(event) => match event {
-                    Event::Key(key) => match key.code {
-                        KeyCode::Esc => {
-                            // on command mode, unfocus and save
-                            self.app_state.mode = AppMode::Command;
-                            self.app_state.blur();
-                            self.view_manager
-                                .editor_component
-                                .sync_buffer_to_state(&mut self.app_state);
-                            save_snippets(&self.app_state.snippet_list[..])?;
-                        }
-                        _ => {
-                            if self.app_state.mode == AppMode::Command {
-                                self.switch_mode(&event);
-                            } else if self.app_state.mode == AppMode::Select {
-                                self.view_manager
-                                    .snippet_list_component
-                                    .handle_event(&event, &mut self.app_state);
-                            } else if self.app_state.mode == AppMode::Edit {
-                                self.view_manager
-                                    .editor_component
-                                    .handle_event(&event, &mut self.app_state);
-                            } else if self.app_state.mode == AppMode::Search {
-                                self.view_manager
-                                    .search_component
-                                    .handle_event(&event, &mut self.app_state);
-                            } else if self.app_state.mode == AppMode::Popup {
-                                self.view_manager
-                                    .add_snippet_popup_component
-                                    .handle_event(&event, &mut self.app_state);
-                            }
-                        }
-                    },
                     _ => {}
-                },
-                Err(_) => {
-                    error!("There was an error trying to read events");
                 }
-            }
-        }
+            })
+            .with_context(|| "could not draw frame")?;
         Ok(())
     }
 }
+
+fn render_confirm_quit(f: &mut Frame, ui_theme: &UiTheme) {
+    let message = "Save the changes before quitting?\n\n[y] Save  [n] Discard  [any] Cancel";
+    let area = centered(f.area(), 44, 5);
+    let block = Block::new()
+        .borders(Borders::ALL)
+        .border_type(ratatui::widgets::BorderType::Rounded)
+        .border_style(ui_theme.border_style())
+        .title(Span::styled(" Unsaved Changes ", ui_theme.title_style()))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Clear, area);
+    Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .style(ui_theme.text_style())
+        .block(block)
+        .render(area, f.buffer_mut());
+}
+
+/// Whether handling `event` can change what's on screen; the mouse moving or keys being
+/// released doesn't.
+fn changes_state(event: &Event) -> bool {
+    match event {
+        Event::Key(key) => key.kind == KeyEventKind::Press,
+        Event::Mouse(mouse) => mouse.kind != MouseEventKind::Moved,
+        _ => true,
+    }
+}
+
+fn render_too_small(f: &mut Frame) {
+    let area = f.area();
+    let message = format!(
+        "Terminal too small: {}x{}\nResize to at least {}x{}",
+        area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+    );
+    let [area] = Layout::vertical([Constraint::Length(2)])
+        .flex(Flex::Center)
+        .areas(area);
+    Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .render(area, f.buffer_mut());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+    use std::{env, fs, path::PathBuf, process};
+
+    fn test_app(name: &str) -> App {
+        let data_dir = env::temp_dir().join(format!("dial-test-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&data_dir);
+        let paths = Paths {
+            config_file: PathBuf::new(),
+            data_dir,
+            log_file: PathBuf::new(),
+            cache_dir: PathBuf::new(),
+        };
+        App::with_events(
+            Config::default(),
+            paths,
+            SyntaxSet::load_defaults_newlines(),
+            ThemeSet::load_defaults(),
+            UiTheme::default(),
+            Events::detached(),
+        )
+    }
+
+    /// Handles the result of the save running in the background.
+    fn finish_running_save(app: &mut App) {
+        let event = app.events.next().unwrap();
+        assert!(matches!(event, AppEvent::Saved(_)));
+        app.handle_app_event(event).unwrap();
+    }
+
+    fn saved_code(app: &App) -> String {
+        load_snippets(&app.paths.data_dir).unwrap()[0].code.clone()
+    }
+
+    fn press(app: &mut App, c: char) {
+        let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        app.handle_app_event(AppEvent::Input(Event::Key(key)))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_autosave() {
+        let mut app = test_app("autosave");
+        app.app_state.set_code(0, String::from("one"));
+        // nothing is saved while the edits go on
+        app.handle_app_event(AppEvent::Tick).unwrap();
+        assert!(!app.saving);
+        app.app_state.last_edit = Some(Instant::now() - AUTOSAVE_DELAY);
+        app.handle_app_event(AppEvent::Tick).unwrap();
+        assert!(app.saving);
+        finish_running_save(&mut app);
+        assert!(!app.app_state.has_unsaved_changes());
+        assert_eq!(saved_code(&app), "one");
+        fs::remove_dir_all(&app.paths.data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_queued_save() {
+        let mut app = test_app("queued-save");
+        app.app_state.set_code(0, String::from("one"));
+        app.save();
+        // an edit during the save is written by the next one
+        app.app_state.set_code(0, String::from("two"));
+        app.save();
+        assert!(app.save_queued);
+        finish_running_save(&mut app);
+        assert!(app.saving && !app.save_queued);
+        assert!(app.app_state.is_modified(0));
+        finish_running_save(&mut app);
+        assert!(!app.app_state.has_unsaved_changes());
+        assert_eq!(saved_code(&app), "two");
+        fs::remove_dir_all(&app.paths.data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_confirm_quit() {
+        let mut app = test_app("confirm-quit");
+        app.app_state.set_code(0, String::from("one"));
+        app.handle_action(Action::Quit).unwrap();
+        assert!(app.confirming_quit);
+        // any other key cancels
+        press(&mut app, 'x');
+        assert!(!app.confirming_quit && !app.app_state.should_exit);
+
+        // the app stays open when the save fails
+        app.handle_action(Action::Quit).unwrap();
+        press(&mut app, 'y');
+        assert!(app.quit_after_save && app.saving);
+        assert!(matches!(app.events.next().unwrap(), AppEvent::Saved(_)));
+        let failed = AppEvent::Saved(Err(anyhow::anyhow!("disk full")));
+        app.handle_app_event(failed).unwrap();
+        assert!(!app.quit_after_save && !app.app_state.should_exit);
+        assert!(app.app_state.is_modified(0));
+
+        // and quits once it succeeded
+        app.handle_action(Action::Quit).unwrap();
+        press(&mut app, 'y');
+        assert!(!app.app_state.should_exit);
+        finish_running_save(&mut app);
+        assert!(app.app_state.should_exit);
+        assert_eq!(saved_code(&app), "one");
+        fs::remove_dir_all(&app.paths.data_dir).unwrap();
+
+        let mut app = test_app("quit-without-saving");
+        app.app_state.set_code(0, String::from("one"));
+        app.handle_action(Action::Quit).unwrap();
+        press(&mut app, 'n');
+        assert!(app.app_state.should_exit && !app.saving);
+        fs::remove_dir_all(&app.paths.data_dir).unwrap();
+    }
+}
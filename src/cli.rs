@@ -0,0 +1,36 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "dial", version, about)]
+pub struct Cli {
+    /// Path to the configuration file.
+    #[arg(long, env = "DIAL_CONFIG", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Directory where snippets.json is stored.
+    #[arg(long, env = "DIAL_DATA_DIR", value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+
+    /// File the log is written to.
+    #[arg(long, value_name = "FILE")]
+    pub log_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print the path of the config file.
+    Config {
+        /// Print the default configuration instead.
+        #[arg(long)]
+        print_default: bool,
+    },
+    /// Open a snippet in $VISUAL or $EDITOR.
+    Edit {
+        /// Position of the snippet in the list, starting from 1.
+        id: usize,
+    },
+}
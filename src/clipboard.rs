@@ -0,0 +1,46 @@
+use log::warn;
+
+/// Copied text, kept in an internal register and mirrored to the system clipboard when one
+/// is available.
+#[derive(Default)]
+pub struct Clipboard {
+    register: String,
+    // connected on first use since connecting to the system clipboard can be slow.
+    system: Option<arboard::Clipboard>,
+    connected: bool,
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, text: String) {
+        if let Some(system) = self.system()
+            && let Err(err) = system.set_text(text.as_str())
+        {
+            warn!("could not copy to the system clipboard: {}", err);
+        }
+        self.register = text;
+    }
+
+    /// Text of the system clipboard, or of the internal register without one.
+    pub fn paste(&mut self) -> String {
+        match self.system().map(|system| system.get_text()) {
+            Some(Ok(text)) => normalize_newlines(&text),
+            _ => self.register.clone(),
+        }
+    }
+
+    fn system(&mut self) -> Option<&mut arboard::Clipboard> {
+        if !self.connected {
+            self.connected = true;
+            match arboard::Clipboard::new() {
+                Ok(system) => self.system = Some(system),
+                Err(err) => warn!("system clipboard is not available: {}", err),
+            }
+        }
+        self.system.as_mut()
+    }
+}
+
+/// Converts windows and old mac line endings of pasted text to `\n`.
+pub fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result, anyhow, bail};
+use directories::ProjectDirs;
+use log::{LevelFilter, info};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use syntect::highlighting::ThemeSet;
+use toml_edit::DocumentMut;
+
+use crate::cli::Cli;
+use crate::indent::IndentStyle;
+use crate::keymap::{KeyProfile, Keymap, KeysConfig};
+
+const CONFIG_FILE: &str = "config.toml";
+const LOG_FILE: &str = "dial.log";
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub data_dir: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub log_level: LevelFilter,
+    pub editor: EditorConfig,
+    pub layout: LayoutConfig,
+    pub ui: UiConfig,
+    pub keys: KeysConfig,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorConfig {
+    /// Width of a tab, and number of spaces inserted by the Tab key.
+    pub tab_size: usize,
+    /// Indent with tabs rather than spaces.
+    pub use_tabs: bool,
+    /// Start new lines at the indentation of the line above.
+    pub auto_indent: bool,
+    /// Close brackets and quotes as they are typed.
+    pub auto_close: bool,
+    /// Initial gap size of the editor's gap buffer.
+    pub buffer_size: usize,
+    /// Padding between the editor border and the code.
+    pub padding: u16,
+    /// Name of the syntect theme used for syntax highlighting.
+    pub theme: String,
+    /// Edit snippets with vim motions and operators.
+    pub vim_mode: bool,
+    /// Wrap long lines at the edge of the editor instead of cutting them.
+    pub soft_wrap: bool,
+    /// Settings by language extension, overriding the ones above. Entries are merged over the
+    /// default ones.
+    #[serde(deserialize_with = "merge_languages")]
+    pub languages: BTreeMap<String, LanguageConfig>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// Indent with tabs rather than spaces.
+    pub use_tabs: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Width of the snippet list, as a percentage of the terminal width.
+    pub list_width: u16,
+    /// Height of the search bar, as a percentage of the terminal height.
+    pub search_height: u16,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Name of the built-in ui theme, one of "dark", "light" or "high-contrast".
+    pub theme: String,
+    /// TOML file overriding colors of the built-in ui theme, relative to the config file.
+    pub theme_file: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: None,
+            log_file: None,
+            log_level: LevelFilter::Info,
+            editor: EditorConfig::default(),
+            layout: LayoutConfig::default(),
+            ui: UiConfig::default(),
+            keys: KeysConfig::default(),
+        }
+    }
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        EditorConfig {
+            tab_size: 4,
+            use_tabs: false,
+            auto_indent: true,
+            auto_close: true,
+            buffer_size: 1024,
+            padding: 1,
+            theme: String::from("base16-eighties.dark"),
+            vim_mode: false,
+            soft_wrap: false,
+            languages: default_languages(),
+        }
+    }
+}
+
+fn default_languages() -> BTreeMap<String, LanguageConfig> {
+    // go is formatted with tabs
+    BTreeMap::from([(String::from("go"), LanguageConfig { use_tabs: true })])
+}
+
+/// Reads the `languages` table over the default one, so that configuring a language keeps the
+/// defaults of the others.
+fn merge_languages<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, LanguageConfig>, D::Error> {
+    let mut languages = default_languages();
+    languages.extend(BTreeMap::<String, LanguageConfig>::deserialize(
+        deserializer,
+    )?);
+    Ok(languages)
+}
+
+impl EditorConfig {
+    /// Indentation of the snippets written in `language`.
+    pub fn indent_style(&self, language: &str) -> IndentStyle {
+        let use_tabs = self
+            .languages
+            .get(language)
+            .map_or(self.use_tabs, |language| language.use_tabs);
+        IndentStyle {
+            use_tabs,
+            width: self.tab_size,
+        }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            list_width: 30,
+            search_height: 10,
+        }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        UiConfig {
+            theme: String::from("dark"),
+            theme_file: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content =
+            fs::read_to_string(path).with_context(|| format!("could not read file {:?}", path))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("could not parse config file {:?}", path))?;
+        let errors = config.validate();
+        if !errors.is_empty() {
+            return Err(anyhow!(errors.join("\n")))
+                .with_context(|| format!("invalid config file {:?}", path));
+        }
+        Ok(config)
+    }
+
+    pub fn print_default() -> Result<String> {
+        let config = Config {
+            keys: KeysConfig::defaults(KeyProfile::Default),
+            ..Config::default()
+        };
+        toml::to_string_pretty(&config).with_context(|| "could not serialize the default config")
+    }
+
+    /// The theme can only be checked once the custom themes have been loaded.
+    pub fn validate_theme(&self, theme_set: &ThemeSet) -> Result<()> {
+        if !theme_set.themes.contains_key(&self.editor.theme) {
+            bail!("editor.theme {:?} does not exist", self.editor.theme);
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !(1..=16).contains(&self.editor.tab_size) {
+            errors.push(String::from("editor.tab_size must be between 1 and 16"));
+        }
+        if self.editor.buffer_size == 0 {
+            errors.push(String::from("editor.buffer_size must be greater than 0"));
+        }
+        if self.editor.padding > 10 {
+            errors.push(String::from("editor.padding must be at most 10"));
+        }
+        if !(10..=90).contains(&self.layout.list_width) {
+            errors.push(String::from("layout.list_width must be between 10 and 90"));
+        }
+        if !(5..=50).contains(&self.layout.search_height) {
+            errors.push(String::from(
+                "layout.search_height must be between 5 and 50",
+            ));
+        }
+        if let Err(keymap_errors) = Keymap::new(&self.keys) {
+            errors.extend(keymap_errors);
+        }
+        errors
+    }
+}
+
+/// Updates `editor.theme` in the config file, keeping the rest of the file untouched.
+pub fn save_theme(path: &Path, theme: &str) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("could not read file {:?}", path))?
+    } else {
+        String::new()
+    };
+    let mut document: DocumentMut = content
+        .parse()
+        .with_context(|| format!("could not parse config file {:?}", path))?;
+    if !document.contains_table("editor") {
+        document["editor"] = toml_edit::table();
+    }
+    document["editor"]["theme"] = toml_edit::value(theme);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("could not create directory {:?}", parent))?;
+    }
+    fs::write(path, document.to_string())
+        .with_context(|| format!("could not write to file {:?}", path))?;
+    info!("saved theme {:?} to {:?}", theme, path);
+    Ok(())
+}
+
+/// Resolved locations of the files dial reads and writes.
+pub struct Paths {
+    pub config_file: PathBuf,
+    pub data_dir: PathBuf,
+    pub log_file: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl Paths {
+    /// Flags and environment variables take precedence over the config file,
+    /// which takes precedence over the system-native directories.
+    pub fn resolve(cli: &Cli, config: &Config, config_file: PathBuf) -> Result<Self> {
+        let project_dirs = project_dirs()?;
+        let data_dir = cli
+            .data_dir
+            .clone()
+            .or_else(|| config.data_dir.clone())
+            .unwrap_or_else(|| project_dirs.data_dir().to_path_buf());
+        let log_file = match cli.log_file.clone().or_else(|| config.log_file.clone()) {
+            Some(log_file) => log_file,
+            None => project_dirs
+                .state_dir()
+                .unwrap_or_else(|| project_dirs.data_local_dir())
+                .join(LOG_FILE),
+        };
+        Ok(Paths {
+            config_file,
+            data_dir,
+            log_file,
+            cache_dir: project_dirs.cache_dir().to_path_buf(),
+        })
+    }
+
+    /// Directory holding additional `.tmTheme` files, next to the config file.
+    pub fn themes_dir(&self) -> PathBuf {
+        self.config_dir().join("themes")
+    }
+
+    /// Directory holding additional `.sublime-syntax` files, next to the config file.
+    pub fn syntaxes_dir(&self) -> PathBuf {
+        self.config_dir().join("syntaxes")
+    }
+
+    /// Directory of the config file.
+    pub fn config_dir(&self) -> &Path {
+        self.config_file.parent().unwrap_or(Path::new("."))
+    }
+}
+
+fn project_dirs() -> Result<ProjectDirs> {
+    match ProjectDirs::from("com", "mouhamadalmounayar", "dial") {
+        Some(project_dirs) => Ok(project_dirs),
+        None => bail!("could not get the path to the project directories"),
+    }
+}
+
+pub fn config_file(cli: &Cli) -> Result<PathBuf> {
+    match &cli.config {
+        Some(path) => Ok(path.clone()),
+        None => Ok(project_dirs()?.config_dir().join(CONFIG_FILE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_languages() {
+        let config: EditorConfig = toml::from_str("[languages.py]\nuse_tabs = true\n").unwrap();
+        assert!(config.languages["py"].use_tabs);
+        assert!(config.languages["go"].use_tabs);
+        let config: EditorConfig = toml::from_str("[languages.go]\nuse_tabs = false\n").unwrap();
+        assert!(!config.indent_style("go").use_tabs);
+    }
+}
@@ -1,9 +1,38 @@
 use log::{error, info};
+use std::{fmt, ops::Range};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// number of undo steps kept by a buffer.
+const UNDO_LIMIT: usize = 1000;
+
+/// Edit of the text, recorded so that it can be undone.
+#[derive(Clone, PartialEq, Debug)]
+enum Change {
+    Insert(usize, String),
+    Delete(usize, String),
+}
+
 pub struct GapBuffer {
     pub buffer: Vec<char>,
     pub capacity: usize,
     pub gap_start: usize,
     pub gap_end: usize,
+    // the line index is split at the gap like the text: newlines before the gap are stored by
+    // index in ascending order, newlines after it by distance from the end of the text so that
+    // they don't shift on edits, the one closest to the gap last.
+    newlines_before: Vec<usize>,
+    newlines_after: Vec<usize>,
+    // first index changed since the last call to take_edited_from.
+    edited_from: Option<usize>,
+    // number of edits so far, telling whether the text changed since some earlier point.
+    revision: usize,
+    // changes since the last commit, undone together, and the committed undo steps.
+    changes: Vec<Change>,
+    undo_steps: Vec<Vec<Change>>,
+    redo_steps: Vec<Vec<Change>>,
+    // off while undoing so that the changes made aren't recorded again.
+    recording: bool,
 }
 
 impl GapBuffer {
@@ -13,19 +42,33 @@ impl GapBuffer {
         let mut buffer: Vec<char> = Vec::with_capacity(capacity + length);
         buffer.extend_from_slice(&chars);
         buffer.resize(capacity + length, '\0');
+        let newlines_before = (0..length).filter(|&i| chars[i] == '\n').collect();
         GapBuffer {
             buffer,
             capacity,
             gap_start: length,
             gap_end: length + capacity - 1,
+            newlines_before,
+            newlines_after: Vec::new(),
+            edited_from: None,
+            revision: 0,
+            changes: Vec::new(),
+            undo_steps: Vec::new(),
+            redo_steps: Vec::new(),
+            recording: true,
         }
     }
     fn move_gap_left(&mut self, index: usize) {
+        let len = self.len();
         while self.gap_start > index {
             self.gap_start -= 1;
             self.gap_end -= 1;
             self.buffer[self.gap_end + 1] = self.buffer[self.gap_start];
             self.buffer[self.gap_start] = '\0';
+            if self.buffer[self.gap_end + 1] == '\n' {
+                self.newlines_before.pop();
+                self.newlines_after.push(len - self.gap_start);
+            }
         }
     }
 
@@ -35,6 +78,10 @@ impl GapBuffer {
             self.gap_end += 1;
             self.buffer[self.gap_start - 1] = self.buffer[self.gap_end];
             self.buffer[self.gap_end] = '\0';
+            if self.buffer[self.gap_start - 1] == '\n' {
+                self.newlines_after.pop();
+                self.newlines_before.push(self.gap_start - 1);
+            }
         }
     }
 
@@ -43,20 +90,24 @@ impl GapBuffer {
         let new_size = new_capacity + self.buffer.len();
         let mut new_buffer = Vec::with_capacity(new_size);
         new_buffer.extend_from_slice(&self.buffer[..self.gap_start]);
-        for _ in 0..new_capacity {
-            new_buffer.push('\0');
-        }
-        let gap_end = new_buffer.len();
+        new_buffer.resize(new_buffer.len() + new_capacity, '\0');
+        let gap_end = new_buffer.len() - 1;
         new_buffer.extend_from_slice(&self.buffer[self.gap_end + 1..]);
         self.gap_end = gap_end;
+        self.capacity = new_capacity;
         self.buffer = new_buffer;
     }
 
     pub fn insert_char(&mut self, c: char) {
         let gap_range = self.gap_end - self.gap_start;
-        if gap_range == 1 {
+        if gap_range <= 1 {
             self.grow();
         }
+        if c == '\n' {
+            self.newlines_before.push(self.gap_start);
+        }
+        self.mark_edited(self.gap_start);
+        self.record_insert(self.gap_start, c);
         self.buffer[self.gap_start] = c;
         self.gap_start += 1;
     }
@@ -66,12 +117,106 @@ impl GapBuffer {
             return;
         }
         self.gap_start -= 1;
+        if self.buffer[self.gap_start] == '\n' {
+            self.newlines_before.pop();
+        }
+        self.mark_edited(self.gap_start);
+        self.record_delete(self.gap_start, self.buffer[self.gap_start]);
         self.buffer[self.gap_start] = '\0';
     }
 
+    /// Deletes the character after the gap.
+    pub fn delete_char_forward(&mut self) {
+        if self.gap_end + 1 >= self.buffer.len() {
+            return;
+        }
+        let c = self.buffer[self.gap_end + 1];
+        if c == '\n' {
+            self.newlines_after.pop();
+        }
+        self.mark_edited(self.gap_start);
+        self.record_delete(self.gap_start, c);
+        self.gap_end += 1;
+        self.buffer[self.gap_end] = '\0';
+    }
+
+    /// Deletes the grapheme cluster before the cursor, such as an emoji with its modifiers.
+    pub fn delete_backward(&mut self) {
+        let start = self.previous_boundary(self.gap_start);
+        self.delete_range(start..self.gap_start);
+    }
+
+    /// Deletes the grapheme cluster after the cursor.
+    pub fn delete_forward(&mut self) {
+        let end = self.next_boundary(self.gap_start);
+        self.delete_range(self.gap_start..end);
+    }
+
+    /// Deletes the word before the cursor along with the non-word characters after it, and
+    /// returns them.
+    pub fn delete_word_backward(&mut self) -> String {
+        let start = self.word_start(self.gap_start);
+        self.delete_range(start..self.gap_start)
+    }
+
+    /// Deletes the word after the cursor along with the non-word characters before it, and
+    /// returns them.
+    pub fn delete_word_forward(&mut self) -> String {
+        let end = self.word_end(self.gap_start);
+        self.delete_range(self.gap_start..end)
+    }
+
+    /// Deletes the line containing `index` with its newline and returns it, leaving the gap at
+    /// the start of the next line.
+    pub fn delete_line(&mut self, index: usize) -> String {
+        let mut start = self.line_start(index);
+        let mut end = self.line_end(index);
+        // the last line has no newline after it, the one before it goes instead
+        if end < self.len() {
+            end += 1;
+        } else {
+            start = start.saturating_sub(1);
+        }
+        let deleted = self.delete_range(start..end);
+        let line_start = self.line_start(start);
+        if line_start != self.gap_start {
+            self.move_gap(line_start);
+        }
+        deleted
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    /// Removes the characters in `range` and returns them, leaving the gap at `range.start`.
+    pub fn delete_range(&mut self, range: Range<usize>) -> String {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        let deleted = self.slice(start..end);
+        // the characters are deleted from the side of the gap to save moving it across them
+        if self.gap_start >= end {
+            if self.gap_start != end {
+                self.move_gap(end);
+            }
+            for _ in start..end {
+                self.delete_char();
+            }
+        } else {
+            if self.gap_start != start {
+                self.move_gap(start);
+            }
+            for _ in start..end {
+                self.delete_char_forward();
+            }
+        }
+        deleted
+    }
+
     pub fn move_gap(&mut self, index: usize) {
-        let gap_size = self.gap_end - self.gap_start;
-        if index + gap_size > self.buffer.len() {
+        if index > self.len() {
             error!("Gap will overflow the buffer if moved to this index.");
             return;
         }
@@ -86,9 +231,333 @@ impl GapBuffer {
             self.move_gap_right(index);
         }
     }
-    pub fn to_string(&self) -> String {
-        self.buffer.iter().filter(|&&c| c != '\0').collect()
+}
+
+impl GapBuffer {
+    /// Number of characters in the text, excluding the gap.
+    pub fn len(&self) -> usize {
+        self.buffer.len() - (self.gap_end + 1 - self.gap_start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Position of the cursor, which always sits at the start of the gap.
+    pub fn cursor(&self) -> usize {
+        self.gap_start
+    }
+
+    /// Character at `index` in the text, skipping over the gap.
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        if index < self.gap_start {
+            Some(self.buffer[index])
+        } else {
+            self.buffer
+                .get(index + self.gap_end + 1 - self.gap_start)
+                .copied()
+        }
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> String {
+        range.filter_map(|index| self.char_at(index)).collect()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.newlines_before.len() + self.newlines_after.len() + 1
+    }
+
+    /// Line containing `index`, counted from 0.
+    pub fn line_of(&self, index: usize) -> usize {
+        let before = self.newlines_before.partition_point(|&i| i < index);
+        // newlines after the gap are before `index` when further from the end
+        let distance = self.len().saturating_sub(index);
+        let after =
+            self.newlines_after.len() - self.newlines_after.partition_point(|&d| d <= distance);
+        before + after
+    }
+
+    /// Index of the first character of line `line`, or the length of the text past the last
+    /// line.
+    pub fn line_start_of(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        let newline = line - 1;
+        if let Some(&index) = self.newlines_before.get(newline) {
+            return index + 1;
+        }
+        let after = newline - self.newlines_before.len();
+        match self.newlines_after.len().checked_sub(after + 1) {
+            Some(position) => self.len() - self.newlines_after[position] + 1,
+            None => self.len(),
+        }
+    }
+
+    fn mark_edited(&mut self, index: usize) {
+        self.revision += 1;
+        self.edited_from = Some(self.edited_from.map_or(index, |from| from.min(index)));
+    }
+
+    /// First index where the text changed since the last call, for caches of the text to be
+    /// updated from there.
+    pub fn take_edited_from(&mut self) -> Option<usize> {
+        self.edited_from.take()
+    }
+
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Start of the grapheme cluster before `index`.
+    pub fn previous_boundary(&self, index: usize) -> usize {
+        if index == 0 {
+            return 0;
+        }
+        // grapheme clusters never span lines, apart from \r\n which ends the line
+        let text = self.slice(self.line_start(index - 1)..index);
+        let last = text.graphemes(true).next_back();
+        index - last.map_or(1, |grapheme| grapheme.chars().count())
+    }
+
+    /// End of the grapheme cluster starting at `index`.
+    pub fn next_boundary(&self, index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        let end = (self.line_end(index) + 1).min(self.len());
+        let text = self.slice(index..end);
+        let first = text.graphemes(true).next();
+        index + first.map_or(1, |grapheme| grapheme.chars().count())
+    }
+
+    /// Start of the word before `index`, skipping anything that isn't part of a word first.
+    pub fn word_start(&self, index: usize) -> usize {
+        let mut index = index;
+        while index > 0 && self.char_at(index - 1).is_some_and(|c| !is_word_char(c)) {
+            index -= 1;
+        }
+        while index > 0 && self.char_at(index - 1).is_some_and(is_word_char) {
+            index -= 1;
+        }
+        index
+    }
+
+    /// End of the word after `index`, skipping anything that isn't part of a word first.
+    pub fn word_end(&self, index: usize) -> usize {
+        let len = self.len();
+        let mut index = index;
+        while index < len && self.char_at(index).is_some_and(|c| !is_word_char(c)) {
+            index += 1;
+        }
+        while index < len && self.char_at(index).is_some_and(is_word_char) {
+            index += 1;
+        }
+        index
+    }
+
+    /// Index of the first character of the line containing `index`.
+    pub fn line_start(&self, index: usize) -> usize {
+        self.line_start_of(self.line_of(index))
+    }
+
+    /// Index of the newline ending the line containing `index`, or the length of the text.
+    pub fn line_end(&self, index: usize) -> usize {
+        let line = self.line_of(index);
+        if line + 1 < self.line_count() {
+            self.line_start_of(line + 1) - 1
+        } else {
+            self.len()
+        }
+    }
+}
+
+impl GapBuffer {
+    fn record_insert(&mut self, index: usize, c: char) {
+        if !self.recording {
+            return;
+        }
+        // typing extends the insertion before it
+        if let Some(Change::Insert(start, text)) = self.changes.last_mut()
+            && *start + text.chars().count() == index
+        {
+            text.push(c);
+            return;
+        }
+        self.changes.push(Change::Insert(index, c.to_string()));
+    }
+
+    fn record_delete(&mut self, index: usize, c: char) {
+        if !self.recording {
+            return;
+        }
+        // deleting backward extends the deletion after it, deleting forward the one at the
+        // same index
+        if let Some(Change::Delete(start, text)) = self.changes.last_mut() {
+            if *start == index + 1 {
+                text.insert(0, c);
+                *start = index;
+                return;
+            }
+            if *start == index {
+                text.push(c);
+                return;
+            }
+        }
+        self.changes.push(Change::Delete(index, c.to_string()));
+    }
+
+    /// Ends the current undo step: the changes made since the last commit are undone together.
+    ///
+    /// A single character typed right after the previous step joins it, so that a word typed
+    /// one key at a time is undone at once.
+    pub fn commit(&mut self) {
+        if self.changes.is_empty() {
+            return;
+        }
+        self.redo_steps.clear();
+        let changes = std::mem::take(&mut self.changes);
+        if let [Change::Insert(index, typed)] = &changes[..]
+            && !typed.contains(char::is_whitespace)
+            && typed.chars().count() == 1
+            && let Some(Change::Insert(start, text)) =
+                self.undo_steps.last_mut().and_then(|step| step.last_mut())
+            && *start + text.chars().count() == *index
+            && !text.ends_with(char::is_whitespace)
+        {
+            text.push_str(typed);
+            return;
+        }
+        if self.undo_steps.len() == UNDO_LIMIT {
+            self.undo_steps.remove(0);
+        }
+        self.undo_steps.push(changes);
+    }
+
+    /// Reverts the last undo step, returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        self.commit();
+        let Some(step) = self.undo_steps.pop() else {
+            return false;
+        };
+        self.recording = false;
+        for change in step.iter().rev() {
+            match change {
+                Change::Insert(index, text) => {
+                    self.delete_range(*index..index + text.chars().count());
+                }
+                Change::Delete(index, text) => {
+                    self.move_gap(*index);
+                    self.insert_str(text);
+                }
+            }
+        }
+        self.recording = true;
+        self.redo_steps.push(step);
+        true
+    }
+
+    /// Applies the last undone step again, returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        self.commit();
+        let Some(step) = self.redo_steps.pop() else {
+            return false;
+        };
+        self.recording = false;
+        for change in &step {
+            match change {
+                Change::Insert(index, text) => {
+                    self.move_gap(*index);
+                    self.insert_str(text);
+                }
+                Change::Delete(index, text) => {
+                    self.delete_range(*index..index + text.chars().count());
+                }
+            }
+        }
+        self.recording = true;
+        self.undo_steps.push(step);
+        true
+    }
+}
+
+impl fmt::Display for GapBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the gap is skipped by index, the text itself may contain any character
+        let before = &self.buffer[..self.gap_start];
+        let after = &self.buffer[self.gap_end + 1..];
+        let text: String = before.iter().chain(after).collect();
+        f.write_str(&text)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Columns taken by `grapheme` when drawn at `column`, tabs reaching the next tab stop.
+pub fn grapheme_width(grapheme: &str, column: usize, tab_size: usize) -> usize {
+    if grapheme == "\t" {
+        tab_size - column % tab_size
+    } else {
+        grapheme.width()
+    }
+}
+
+/// Columns taken by `text` when drawn from the start of a line.
+pub fn display_width(text: &str, tab_size: usize) -> usize {
+    text.graphemes(true).fold(0, |column, grapheme| {
+        column + grapheme_width(grapheme, column, tab_size)
+    })
+}
+
+/// Character offset in `line` of the grapheme drawn at `column`, or of the end of the line
+/// when it is shorter.
+pub fn column_index(line: &str, column: usize, tab_size: usize) -> usize {
+    let mut index = 0;
+    let mut width = 0;
+    for grapheme in line.graphemes(true) {
+        width += grapheme_width(grapheme, width, tab_size);
+        if width > column {
+            break;
+        }
+        index += grapheme.chars().count();
+    }
+    index
+}
+
+/// Character offsets where the rows of `line` start once wrapped to `width` columns, rows
+/// breaking after whitespace when they have some.
+pub fn wrap_line(line: &str, width: usize, tab_size: usize) -> Vec<usize> {
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    let mut offsets = Vec::with_capacity(graphemes.len());
+    let mut offset = 0;
+    for grapheme in &graphemes {
+        offsets.push(offset);
+        offset += grapheme.chars().count();
+    }
+    let mut starts = vec![0];
+    // first grapheme of the current row, and the one following its last whitespace
+    let mut row_start = 0;
+    let mut row_break = None;
+    let mut column = 0;
+    for (i, grapheme) in graphemes.iter().enumerate() {
+        let mut width_here = grapheme_width(grapheme, column, tab_size);
+        while column > 0 && column + width_here > width {
+            row_start = row_break.filter(|&start| start > row_start).unwrap_or(i);
+            row_break = None;
+            starts.push(offsets[row_start]);
+            column = graphemes[row_start..i].iter().fold(0, |column, grapheme| {
+                column + grapheme_width(grapheme, column, tab_size)
+            });
+            width_here = grapheme_width(grapheme, column, tab_size);
+        }
+        column += width_here;
+        if grapheme.chars().all(char::is_whitespace) {
+            row_break = Some(i + 1);
+        }
     }
+    starts
 }
 
 #[cfg(test)]
@@ -161,6 +630,200 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_nul_characters() {
+        let mut gap_buffer = GapBuffer::from_str("a\0b", 2);
+        gap_buffer.move_gap(1);
+        gap_buffer.insert_char('\0');
+        assert_eq!(gap_buffer.to_string(), "a\0\0b");
+        assert_eq!(gap_buffer.len(), 4);
+    }
+
+    #[test]
+    fn test_graphemes() {
+        // e with a combining accent, a family emoji and a flag
+        let mut gap_buffer = GapBuffer::from_str("e\u{301}👨\u{200d}👩\u{200d}👧🇫🇷\r\n", 4);
+        assert_eq!(gap_buffer.next_boundary(0), 2);
+        assert_eq!(gap_buffer.next_boundary(2), 7);
+        assert_eq!(gap_buffer.next_boundary(7), 9);
+        assert_eq!(gap_buffer.next_boundary(9), 11);
+        assert_eq!(gap_buffer.previous_boundary(11), 9);
+        assert_eq!(gap_buffer.previous_boundary(7), 2);
+        gap_buffer.move_gap(9);
+        gap_buffer.delete_backward();
+        assert_eq!(gap_buffer.to_string(), "e\u{301}👨\u{200d}👩\u{200d}👧\r\n");
+        gap_buffer.delete_backward();
+        gap_buffer.delete_backward();
+        assert_eq!(gap_buffer.to_string(), "\r\n");
+    }
+
+    #[test]
+    fn test_revision() {
+        let mut gap_buffer = GapBuffer::from_str("abc", 4);
+        gap_buffer.move_gap(1);
+        assert_eq!(gap_buffer.revision(), 0);
+        gap_buffer.insert_str("xy");
+        gap_buffer.delete_backward();
+        assert_eq!(gap_buffer.revision(), 3);
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut gap_buffer = GapBuffer::from_str("one", 4);
+        gap_buffer.move_gap(3);
+        for c in " two".chars() {
+            gap_buffer.insert_char(c);
+            gap_buffer.commit();
+        }
+        gap_buffer.delete_range(0..4);
+        gap_buffer.commit();
+        assert_eq!(gap_buffer.to_string(), "two");
+
+        assert!(gap_buffer.undo());
+        assert_eq!(gap_buffer.to_string(), "one two");
+        assert!(gap_buffer.undo());
+        assert_eq!(gap_buffer.to_string(), "one ");
+        assert!(gap_buffer.undo());
+        assert_eq!(gap_buffer.to_string(), "one");
+        assert!(!gap_buffer.undo());
+
+        assert!(gap_buffer.redo());
+        assert!(gap_buffer.redo());
+        assert_eq!(gap_buffer.to_string(), "one two");
+        gap_buffer.insert_char('\n');
+        gap_buffer.commit();
+        assert!(!gap_buffer.redo());
+        assert!(gap_buffer.undo());
+        assert_eq!(gap_buffer.to_string(), "one two");
+    }
+
+    #[test]
+    fn test_delete_forward() {
+        let mut gap_buffer = GapBuffer::from_str("a\nbe\u{301}c", 2);
+        gap_buffer.move_gap(0);
+        gap_buffer.delete_char_forward();
+        assert_eq!(gap_buffer.to_string(), "\nbe\u{301}c");
+        gap_buffer.delete_forward();
+        assert_eq!(gap_buffer.line_count(), 1);
+        gap_buffer.move_gap(1);
+        gap_buffer.delete_forward();
+        assert_eq!(gap_buffer.to_string(), "bc");
+        gap_buffer.move_gap(2);
+        gap_buffer.delete_forward();
+        assert_eq!(
+            (gap_buffer.to_string().as_str(), gap_buffer.cursor()),
+            ("bc", 2)
+        );
+
+        gap_buffer.commit();
+        gap_buffer.undo();
+        assert_eq!(gap_buffer.to_string(), "a\nbe\u{301}c");
+    }
+
+    #[test]
+    fn test_delete_range() {
+        let mut gap_buffer = GapBuffer::from_str("one\ntwo\nthree", 4);
+        gap_buffer.move_gap(2);
+        assert_eq!(gap_buffer.delete_range(6..10), "o\nth");
+        assert_eq!(gap_buffer.to_string(), "one\ntwree");
+        assert_eq!((gap_buffer.cursor(), gap_buffer.line_count()), (6, 2));
+        assert_eq!(gap_buffer.delete_range(8..20), "e");
+        assert_eq!(gap_buffer.to_string(), "one\ntwre");
+    }
+
+    #[test]
+    fn test_delete_line() {
+        let mut gap_buffer = GapBuffer::from_str("one\ntwo\nthree", 4);
+        assert_eq!(gap_buffer.delete_line(5), "two\n");
+        assert_eq!(
+            (gap_buffer.to_string().as_str(), gap_buffer.cursor()),
+            ("one\nthree", 4)
+        );
+        assert_eq!(gap_buffer.delete_line(6), "\nthree");
+        assert_eq!(
+            (gap_buffer.to_string().as_str(), gap_buffer.cursor()),
+            ("one", 0)
+        );
+        assert_eq!(gap_buffer.delete_line(1), "one");
+        assert!(gap_buffer.is_empty());
+        assert_eq!(gap_buffer.line_count(), 1);
+    }
+
+    #[test]
+    fn test_delete_word() {
+        let mut gap_buffer = GapBuffer::from_str("let foo_bar = 1;", 4);
+        gap_buffer.move_gap(14);
+        assert_eq!(gap_buffer.delete_word_backward(), "foo_bar = ");
+        assert_eq!(gap_buffer.to_string(), "let 1;");
+        gap_buffer.move_gap(0);
+        assert_eq!(gap_buffer.delete_word_forward(), "let");
+        assert_eq!(gap_buffer.delete_word_forward(), " 1");
+        assert_eq!(gap_buffer.delete_word_forward(), ";");
+        assert_eq!(gap_buffer.delete_word_backward(), "");
+        assert!(gap_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_line_index() {
+        let mut gap_buffer = GapBuffer::from_str("ab\ncd\n\nef", 2);
+        let check = |gap_buffer: &GapBuffer| {
+            let text = gap_buffer.to_string();
+            assert_eq!(gap_buffer.line_count(), text.split('\n').count());
+            for index in 0..=text.chars().count() {
+                let line = text.chars().take(index).filter(|&c| c == '\n').count();
+                assert_eq!(gap_buffer.line_of(index), line, "line of {}", index);
+            }
+            let mut start = 0;
+            for (line, content) in text.split('\n').enumerate() {
+                assert_eq!(gap_buffer.line_start_of(line), start);
+                assert_eq!(gap_buffer.line_end(start), start + content.chars().count());
+                start += content.chars().count() + 1;
+            }
+        };
+        check(&gap_buffer);
+        gap_buffer.move_gap(4);
+        check(&gap_buffer);
+        gap_buffer.insert_str("x\ny\n");
+        check(&gap_buffer);
+        gap_buffer.move_gap(1);
+        gap_buffer.delete_char();
+        check(&gap_buffer);
+        gap_buffer.move_gap(8);
+        gap_buffer.delete_range(2..5);
+        check(&gap_buffer);
+        gap_buffer.move_gap(gap_buffer.len());
+        check(&gap_buffer);
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("ab", 4), 2);
+        assert_eq!(display_width("日本", 4), 4);
+        assert_eq!(display_width("a\tb", 4), 5);
+        assert_eq!(display_width("\t\t", 4), 8);
+        assert_eq!(display_width("e\u{301}", 4), 1);
+    }
+
+    #[test]
+    fn test_wrap_line() {
+        assert_eq!(wrap_line("one two three", 8, 4), vec![0, 8]);
+        assert_eq!(wrap_line("abcdefghij", 4, 4), vec![0, 4, 8]);
+        assert_eq!(wrap_line("ab cdefgh", 4, 4), vec![0, 3, 7]);
+        assert_eq!(wrap_line("日本語", 4, 4), vec![0, 2]);
+        assert_eq!(wrap_line("a\tb", 4, 4), vec![0, 2]);
+        assert_eq!(wrap_line("", 4, 4), vec![0]);
+    }
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(column_index("日本語", 3, 4), 1);
+        assert_eq!(column_index("日本語", 4, 4), 2);
+        assert_eq!(column_index("\tab", 2, 4), 0);
+        assert_eq!(column_index("\tab", 5, 4), 2);
+        assert_eq!(column_index("e\u{301}x", 1, 4), 2);
+        assert_eq!(column_index("ab", 10, 4), 2);
+    }
+
     #[test]
     fn test_insert_with_move() {
         let mut gap_buffer = GapBuffer::from_str("Hello", 2);
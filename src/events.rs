@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use log::error;
+use ratatui::crossterm::event::{self, Event};
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+/// Everything the main loop waits for.
+pub enum AppEvent {
+    /// Event read from the terminal.
+    Input(Event),
+    /// Sent at a fixed rate so that timers expire without any input.
+    Tick,
+    /// Result of saving the snippets in the background.
+    Saved(Result<()>),
+}
+
+// how long the input thread waits for an event before checking whether it has to pause.
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Channel fed by the terminal input, the tick timer and the background jobs.
+pub struct Events {
+    sender: Sender<AppEvent>,
+    receiver: Receiver<AppEvent>,
+    // pauses the input thread, which answers once it stopped reading.
+    pause: Sender<Sender<()>>,
+    resume: Sender<()>,
+}
+
+impl Events {
+    pub fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let input = sender.clone();
+        let (pause, paused) = mpsc::channel();
+        let (resume, resumed) = mpsc::channel();
+        thread::spawn(move || read_input(input, paused, resumed));
+        let ticks = sender.clone();
+        thread::spawn(move || {
+            // stops once the receiver is dropped
+            while ticks.send(AppEvent::Tick).is_ok() {
+                thread::sleep(tick_rate);
+            }
+        });
+        Events {
+            sender,
+            receiver,
+            pause,
+            resume,
+        }
+    }
+
+    /// Channel fed only by the background jobs, without reading the terminal or ticking.
+    #[cfg(test)]
+    pub fn detached() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (pause, _) = mpsc::channel();
+        let (resume, _) = mpsc::channel();
+        Events {
+            sender,
+            receiver,
+            pause,
+            resume,
+        }
+    }
+
+    /// Stops reading the terminal, for another program to use it.
+    pub fn pause_input(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.pause.send(done).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    pub fn resume_input(&self) {
+        let _ = self.resume.send(());
+    }
+
+    /// Blocks until the next event.
+    pub fn next(&self) -> Result<AppEvent> {
+        self.receiver
+            .recv()
+            .with_context(|| "could not receive events")
+    }
+
+    /// Runs `job` on another thread, the event it returns is received like any other.
+    pub fn spawn(&self, job: impl FnOnce() -> AppEvent + Send + 'static) {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send(job());
+        });
+    }
+}
+
+fn read_input(sender: Sender<AppEvent>, paused: Receiver<Sender<()>>, resumed: Receiver<()>) {
+    loop {
+        if let Ok(done) = paused.try_recv() {
+            let _ = done.send(());
+            if resumed.recv().is_err() {
+                return;
+            }
+        }
+        match event::poll(POLL_TIMEOUT) {
+            Ok(false) => continue,
+            Ok(true) => {}
+            Err(err) => {
+                error!("could not poll terminal events: {}", err);
+                continue;
+            }
+        }
+        match event::read() {
+            Ok(event) => {
+                if sender.send(AppEvent::Input(event)).is_err() {
+                    return;
+                }
+            }
+            Err(err) => error!("could not read terminal events: {}", err),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result, anyhow, bail};
+use log::{error, info};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::{
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, enable_raw_mode},
+};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write, stdout},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// names tried for the temporary file before giving up.
+const TEMP_FILE_ATTEMPTS: usize = 16;
+
+/// Enters the alternate screen with bracketed paste and mouse capture, once at startup as it
+/// also installs the panic hook restoring the terminal.
+pub fn init_terminal() -> DefaultTerminal {
+    let terminal = ratatui::init();
+    enable_input();
+    terminal
+}
+
+/// Enters the alternate screen again after [`restore_terminal`], and redraws it from scratch.
+pub fn resume_terminal(terminal: &mut DefaultTerminal) -> Result<()> {
+    enable_raw_mode().context("could not enable raw mode")?;
+    execute!(stdout(), EnterAlternateScreen).context("could not enter the alternate screen")?;
+    enable_input();
+    terminal.clear().context("could not clear the terminal")
+}
+
+fn enable_input() {
+    if let Err(err) = execute!(stdout(), EnableBracketedPaste, EnableMouseCapture) {
+        error!(
+            "could not enable bracketed paste and mouse capture: {}",
+            err
+        );
+    }
+}
+
+/// Gives the terminal back in the state it was found in.
+pub fn restore_terminal() {
+    if let Err(err) = execute!(stdout(), DisableBracketedPaste, DisableMouseCapture) {
+        error!(
+            "could not disable bracketed paste and mouse capture: {}",
+            err
+        );
+    }
+    ratatui::restore();
+}
+
+/// Command of the user's editor, from `$VISUAL` or `$EDITOR`.
+pub fn editor_command() -> Result<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .ok_or_else(|| anyhow!("neither $VISUAL nor $EDITOR is set"))
+}
+
+/// Opens `code` in the editor `command` and returns the text it was saved with.
+///
+/// The code is written to a temporary file named after `language` so that the editor picks
+/// the right syntax. `command` may hold arguments, such as `code --wait`.
+pub fn edit(command: &str, code: &str, language: &str) -> Result<String> {
+    let path = write_temp_file(code, language)?;
+    let result = run_editor(command, &path).and_then(|()| {
+        fs::read_to_string(&path).with_context(|| format!("could not read file {:?}", path))
+    });
+    if let Err(err) = fs::remove_file(&path) {
+        error!("could not remove file {:?}: {}", path, err);
+    }
+    result
+}
+
+fn run_editor(command: &str, path: &Path) -> Result<()> {
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow!("the editor command is empty"))?;
+    info!("opening {:?} with {}", path, command);
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("could not run the editor {:?}", command))?;
+    if !status.success() {
+        bail!("the editor {:?} exited with {}", command, status);
+    }
+    Ok(())
+}
+
+/// Writes `code` to a new temporary file that only the user can read, and returns its path.
+///
+/// The file is created rather than opened, so that a file put in its place beforehand is
+/// never written to.
+fn write_temp_file(code: &str, language: &str) -> Result<PathBuf> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    for _ in 0..TEMP_FILE_ATTEMPTS {
+        let path = temp_file(language);
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not create file {:?}", path));
+            }
+        };
+        if let Err(err) = file.write_all(code.as_bytes()) {
+            if let Err(err) = fs::remove_file(&path) {
+                error!("could not remove file {:?}: {}", path, err);
+            }
+            return Err(err).with_context(|| format!("could not write file {:?}", path));
+        }
+        return Ok(path);
+    }
+    bail!("could not create a temporary file in {:?}", env::temp_dir())
+}
+
+fn temp_file(language: &str) -> PathBuf {
+    // the language is the extension of the snippet, unless it can't be one
+    let extension = if language.is_empty() || language.contains(['/', '\\', '.']) {
+        "txt"
+    } else {
+        language
+    };
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos());
+    env::temp_dir().join(format!(
+        "dial-{}-{}.{}",
+        std::process::id(),
+        nanos,
+        extension
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit() {
+        // the editor is a script rewriting the file it is given
+        let script = temp_file("sh");
+        fs::write(
+            &script,
+            "sed s/foo/bar/ \"$1\" > \"$1.new\" && mv \"$1.new\" \"$1\"\n",
+        )
+        .unwrap();
+        let edited = edit(&format!("sh {}", script.display()), "let foo = 1;\n", "rs");
+        fs::remove_file(&script).unwrap();
+        assert_eq!(edited.unwrap(), "let bar = 1;\n");
+        assert!(edit("false", "code", "rs").is_err());
+        assert_eq!(temp_file("../x").extension().unwrap(), "txt");
+        assert_eq!(temp_file("py").extension().unwrap(), "py");
+    }
+
+    #[test]
+    fn test_write_temp_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = write_temp_file("code", "rs").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
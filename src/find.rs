@@ -0,0 +1,236 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+use crate::editor::GapBuffer;
+
+const FIND_BUFFER_SIZE: usize = 256;
+
+type Matches = Result<Vec<Range<usize>>, regex::Error>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FindInput {
+    Query,
+    Replacement,
+}
+
+/// Find and replace bar of the editor.
+pub struct FindBar {
+    pub query: GapBuffer,
+    pub replacement: GapBuffer,
+    pub focused_input: FindInput,
+    /// Whether the query is a regular expression rather than plain text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    // matches of the last search, with what they were searched with
+    cache: Option<(MatchKey, Matches)>,
+}
+
+/// Buffer and query the cached matches were found with.
+#[derive(PartialEq)]
+struct MatchKey {
+    buffer: usize,
+    revision: usize,
+    query: String,
+    regex: bool,
+    case_sensitive: bool,
+}
+
+impl FindBar {
+    pub fn new() -> Self {
+        FindBar {
+            query: GapBuffer::from_str("", FIND_BUFFER_SIZE),
+            replacement: GapBuffer::from_str("", FIND_BUFFER_SIZE),
+            focused_input: FindInput::Query,
+            regex: false,
+            case_sensitive: false,
+            cache: None,
+        }
+    }
+
+    pub fn input_mut(&mut self) -> &mut GapBuffer {
+        match self.focused_input {
+            FindInput::Query => &mut self.query,
+            FindInput::Replacement => &mut self.replacement,
+        }
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.query = GapBuffer::from_str(query, FIND_BUFFER_SIZE.max(query.len()));
+    }
+
+    pub fn toggle_input(&mut self) {
+        self.focused_input = match self.focused_input {
+            FindInput::Query => FindInput::Replacement,
+            FindInput::Replacement => FindInput::Query,
+        };
+    }
+
+    /// Pattern of the query, none when it is empty.
+    fn pattern(&self) -> Result<Option<Regex>, regex::Error> {
+        let query = self.query.to_string();
+        if query.is_empty() {
+            return Ok(None);
+        }
+        let pattern = if self.regex {
+            query
+        } else {
+            regex::escape(&query)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .build()
+            .map(Some)
+    }
+
+    /// Matches of the query in the buffer identified by `id`, searched again only once the
+    /// buffer or the query changed.
+    pub fn buffer_matches(&mut self, id: usize, buffer: &GapBuffer) -> &Matches {
+        let key = MatchKey {
+            buffer: id,
+            revision: buffer.revision(),
+            query: self.query.to_string(),
+            regex: self.regex,
+            case_sensitive: self.case_sensitive,
+        };
+        if self.cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let matches = self.matches(&buffer.to_string());
+            self.cache = Some((key, matches));
+        }
+        &self
+            .cache
+            .as_ref()
+            .expect("unexpected state cache must not be null")
+            .1
+    }
+
+    /// Drops the cached matches, for a buffer whose text was replaced as a whole.
+    pub fn forget_matches(&mut self) {
+        self.cache = None;
+    }
+
+    /// Character ranges of the matches of the query in `text`, empty matches left out.
+    pub fn matches(&self, text: &str) -> Matches {
+        let Some(pattern) = self.pattern()? else {
+            return Ok(Vec::new());
+        };
+        // byte offsets are converted to characters in a single pass
+        let mut matches = Vec::new();
+        let (mut byte, mut index) = (0, 0);
+        for found in pattern.find_iter(text) {
+            if found.is_empty() {
+                continue;
+            }
+            index += text[byte..found.start()].chars().count();
+            let start = index;
+            index += found.as_str().chars().count();
+            byte = found.end();
+            matches.push(start..index);
+        }
+        Ok(matches)
+    }
+
+    /// Text replacing the match at `range` of `text`, with the groups of a regular expression
+    /// such as `$1` expanded.
+    pub fn replacement_for(&self, text: &str, range: Range<usize>) -> String {
+        let replacement = self.replacement.to_string();
+        if !self.regex {
+            return replacement;
+        }
+        let Ok(Some(pattern)) = self.pattern() else {
+            return replacement;
+        };
+        let start = text
+            .char_indices()
+            .nth(range.start)
+            .map_or(text.len(), |(byte, _)| byte);
+        // searched from the start of the match so that anchors still see the text before it
+        match pattern.captures_at(text, start) {
+            Some(captures) => {
+                let mut expanded = String::new();
+                captures.expand(&replacement, &mut expanded);
+                expanded
+            }
+            None => replacement,
+        }
+    }
+}
+
+/// Match after `index`, wrapping around to the first one.
+pub fn next_match(matches: &[Range<usize>], index: usize) -> Option<Range<usize>> {
+    matches
+        .iter()
+        .find(|found| found.start >= index)
+        .or(matches.first())
+        .cloned()
+}
+
+/// Match before `index`, wrapping around to the last one.
+pub fn previous_match(matches: &[Range<usize>], index: usize) -> Option<Range<usize>> {
+    matches
+        .iter()
+        .rev()
+        .find(|found| found.start < index)
+        .or(matches.last())
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_bar(query: &str, replacement: &str) -> FindBar {
+        let mut find = FindBar::new();
+        find.query.insert_str(query);
+        find.replacement.insert_str(replacement);
+        find
+    }
+
+    #[test]
+    fn test_matches() {
+        let text = "let é = Foo(foo);";
+        let mut find = find_bar("foo", "");
+        assert_eq!(find.matches(text).unwrap(), vec![8..11, 12..15]);
+        find.case_sensitive = true;
+        assert_eq!(find.matches(text).unwrap(), vec![12..15]);
+        let find = find_bar("(", "");
+        assert_eq!(find.matches(text).unwrap(), vec![11..12]);
+        let mut find = find_bar("(", "");
+        find.regex = true;
+        assert!(find.matches(text).is_err());
+        let mut find = find_bar("^\\w*", "");
+        find.regex = true;
+        assert_eq!(find.matches("ab\n\ncd").unwrap(), vec![0..2, 4..6]);
+        assert!(find_bar("", "").matches(text).unwrap().is_empty());
+
+        let matches = [2..4, 6..8];
+        assert_eq!(next_match(&matches, 4), Some(6..8));
+        assert_eq!(next_match(&matches, 7), Some(2..4));
+        assert_eq!(previous_match(&matches, 6), Some(2..4));
+        assert_eq!(previous_match(&matches, 2), Some(6..8));
+    }
+
+    #[test]
+    fn test_buffer_matches() {
+        let mut find = find_bar("ab", "");
+        let mut buffer = GapBuffer::from_str("ab ab", 8);
+        assert_eq!(find.buffer_matches(0, &buffer).as_ref().unwrap().len(), 2);
+        buffer.insert_str(" ab");
+        assert_eq!(find.buffer_matches(0, &buffer).as_ref().unwrap().len(), 3);
+        find.query.delete_backward();
+        assert_eq!(find.buffer_matches(0, &buffer).as_ref().unwrap().len(), 3);
+        find.regex = true;
+        find.query.insert_str("+(");
+        assert!(find.buffer_matches(0, &buffer).is_err());
+    }
+
+    #[test]
+    fn test_replacement() {
+        let text = "é fn(a, b) fn(c, d)";
+        let mut find = find_bar("fn\\((\\w), (\\w)\\)", "fn($2, $1)");
+        find.regex = true;
+        assert_eq!(find.replacement_for(text, 11..19), "fn(d, c)");
+        find.regex = false;
+        assert_eq!(find.replacement_for(text, 11..19), "fn($2, $1)");
+    }
+}
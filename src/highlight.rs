@@ -0,0 +1,246 @@
+use crate::editor::GapBuffer;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use ratatui::{
+    style::Color,
+    text::{Line, Span},
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use syntect::{
+    dumps::{dump_to_file, from_dump_file},
+    highlighting::{HighlightIterator, HighlightState, Highlighter, Theme, ThemeSet},
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
+};
+use syntect_tui::into_span;
+
+const SYNTAX_DUMP_FILE: &str = "syntaxes.packdump";
+const SYNTAX_FINGERPRINT_FILE: &str = "syntaxes.fingerprint";
+
+/// Loads the built-in syntect themes along with any `.tmTheme` files found in `themes_dir`.
+pub fn load_theme_set(themes_dir: &Path) -> Result<ThemeSet> {
+    let mut theme_set = ThemeSet::load_defaults();
+    if themes_dir.is_dir() {
+        theme_set
+            .add_from_folder(themes_dir)
+            .with_context(|| format!("could not load themes from {:?}", themes_dir))?;
+        info!("loaded themes from {:?}", themes_dir);
+    }
+    Ok(theme_set)
+}
+
+/// Loads the built-in syntaxes merged with any `.sublime-syntax` files found in `syntaxes_dir`.
+///
+/// Compiling syntax definitions is slow, so the merged set is dumped to `cache_dir` and reused
+/// until the files in `syntaxes_dir` change.
+pub fn load_syntax_set(syntaxes_dir: &Path, cache_dir: &Path) -> Result<SyntaxSet> {
+    if !syntaxes_dir.is_dir() {
+        return Ok(SyntaxSet::load_defaults_nonewlines());
+    }
+    let dump_path = cache_dir.join(SYNTAX_DUMP_FILE);
+    let fingerprint_path = cache_dir.join(SYNTAX_FINGERPRINT_FILE);
+    let fingerprint = fingerprint(syntaxes_dir)?;
+
+    let cached_fingerprint = fs::read_to_string(&fingerprint_path).unwrap_or_default();
+    if cached_fingerprint == fingerprint {
+        match from_dump_file(&dump_path) {
+            Ok(syntax_set) => {
+                info!("loaded cached syntaxes from {:?}", dump_path);
+                return Ok(syntax_set);
+            }
+            Err(err) => warn!("could not load cached syntaxes {:?}: {}", dump_path, err),
+        }
+    }
+
+    let mut builder = SyntaxSet::load_defaults_nonewlines().into_builder();
+    builder
+        .add_from_folder(syntaxes_dir, false)
+        .with_context(|| format!("could not load syntaxes from {:?}", syntaxes_dir))?;
+    let syntax_set = builder.build();
+    info!("loaded syntaxes from {:?}", syntaxes_dir);
+
+    // a missing cache only slows down the next startup, so failures are not fatal.
+    let cache_result = fs::create_dir_all(cache_dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| dump_to_file(&syntax_set, &dump_path).map_err(anyhow::Error::from))
+        .and_then(|_| fs::write(&fingerprint_path, &fingerprint).map_err(anyhow::Error::from));
+    if let Err(err) = cache_result {
+        warn!("could not cache syntaxes to {:?}: {}", cache_dir, err);
+    }
+    Ok(syntax_set)
+}
+
+/// Summarizes the name, size and modification time of every syntax definition in `dir`.
+fn fingerprint(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_syntax_files(dir, &mut files)?;
+    files.sort();
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    for file in files {
+        let metadata = fs::metadata(&file)
+            .with_context(|| format!("could not read metadata of {:?}", file))?;
+        file.hash(&mut hasher);
+        metadata.len().hash(&mut hasher);
+        metadata.modified().ok().hash(&mut hasher);
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+fn collect_syntax_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("could not read directory {:?}", dir))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_syntax_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "sublime-syntax") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Highlighted lines of a buffer, kept across frames.
+///
+/// The parser state at the start of every line is remembered, so that an edit only requires
+/// highlighting again from the edited line, and only up to the end of the viewport.
+#[derive(Default)]
+pub struct HighlightCache {
+    // syntax and theme the cache was built with.
+    source: Option<(String, String)>,
+    // state at the start of each line, one more than there are lines.
+    states: Vec<(ParseState, HighlightState)>,
+    // highlighted lines, None for lines that were only parsed on the way to the viewport.
+    lines: Vec<Option<Line<'static>>>,
+}
+
+impl HighlightCache {
+    pub fn clear(&mut self) {
+        self.source = None;
+        self.states.clear();
+        self.lines.clear();
+    }
+
+    /// Drops what was computed for `line` and the lines after it.
+    pub fn invalidate_from(&mut self, line: usize) {
+        self.lines.truncate(line);
+        self.states.truncate(line + 1);
+    }
+
+    /// Highlights the lines of `buffer` in `visible`.
+    pub fn highlight(
+        &mut self,
+        buffer: &GapBuffer,
+        visible: Range<usize>,
+        syntax_set: &SyntaxSet,
+        syntax: &SyntaxReference,
+        theme: (&str, &Theme),
+    ) -> Vec<Line<'static>> {
+        let (theme_name, theme) = theme;
+        let source = (syntax.name.clone(), theme_name.to_string());
+        if self.source.as_ref() != Some(&source) {
+            self.clear();
+            self.source = Some(source);
+        }
+        let highlighter = Highlighter::new(theme);
+        if self.states.is_empty() {
+            let highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
+            self.states.push((ParseState::new(syntax), highlight_state));
+        }
+        let line_text =
+            |line: usize| buffer.slice(buffer.line_start_of(line)..buffer.line_start_of(line + 1));
+        // lines above the viewport are parsed for their state but not kept.
+        while self.lines.len() < visible.end {
+            let line = self.lines.len();
+            let mut state = self.states[line].clone();
+            let highlighted = highlight_line(
+                &line_text(line),
+                &mut state,
+                &highlighter,
+                syntax_set,
+                visible.contains(&line),
+            );
+            self.states.push(state);
+            self.lines.push(highlighted);
+        }
+        visible
+            .map(|line| {
+                if self.lines[line].is_none() {
+                    let mut state = self.states[line].clone();
+                    self.lines[line] = highlight_line(
+                        &line_text(line),
+                        &mut state,
+                        &highlighter,
+                        syntax_set,
+                        true,
+                    );
+                }
+                self.lines[line].clone().unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+/// Advances `state` past `text`, returning the highlighted line when `keep` is set.
+fn highlight_line(
+    text: &str,
+    state: &mut (ParseState, HighlightState),
+    highlighter: &Highlighter,
+    syntax_set: &SyntaxSet,
+    keep: bool,
+) -> Option<Line<'static>> {
+    let (parse_state, highlight_state) = state;
+    let ops = match parse_state.parse_line(text, syntax_set) {
+        Ok(ops) => ops,
+        Err(err) => {
+            warn!("could not parse line {:?}: {}", text, err);
+            Vec::new()
+        }
+    };
+    let segments = HighlightIterator::new(highlight_state, &ops, text, highlighter);
+    if !keep {
+        segments.for_each(drop);
+        return None;
+    }
+    let spans: Vec<Span<'static>> = segments
+        .filter_map(|segment| into_span(segment).ok())
+        // override underline color style and background
+        .map(|span| {
+            let style = span.style.underline_color(Color::Reset).bg(Color::Reset);
+            Span::styled(span.content.into_owned(), style)
+        })
+        .collect();
+    Some(Line::from(spans))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_highlighting() {
+        let syntax_set = SyntaxSet::load_defaults_nonewlines();
+        let theme_set = ThemeSet::load_defaults();
+        let theme = ("base16-ocean.dark", &theme_set.themes["base16-ocean.dark"]);
+        let syntax = syntax_set.find_syntax_by_extension("rs").unwrap();
+        let mut buffer = GapBuffer::from_str("fn main() {\n    let a = 1;\n}\n", 8);
+        let mut cache = HighlightCache::default();
+        cache.highlight(&buffer, 1..3, &syntax_set, syntax, theme);
+
+        // opening a comment on the first line changes how every line after it is highlighted
+        buffer.move_gap(0);
+        buffer.insert_str("/*");
+        let edited_from = buffer.take_edited_from().unwrap();
+        cache.invalidate_from(buffer.line_of(edited_from));
+        let incremental = cache.highlight(&buffer, 0..4, &syntax_set, syntax, theme);
+        let full = HighlightCache::default().highlight(&buffer, 0..4, &syntax_set, syntax, theme);
+        assert_eq!(incremental, full);
+        assert_eq!(buffer.take_edited_from(), None);
+    }
+}
@@ -0,0 +1,333 @@
+use std::ops::Range;
+
+use crate::editor::GapBuffer;
+
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+/// How a language is indented.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IndentStyle {
+    pub use_tabs: bool,
+    /// Width of a level in spaces, also used to dedent lines indented with spaces.
+    pub width: usize,
+}
+
+impl IndentStyle {
+    /// Text inserted for one level of indentation.
+    pub fn unit(self) -> String {
+        if self.use_tabs {
+            String::from("\t")
+        } else {
+            " ".repeat(self.width)
+        }
+    }
+}
+
+fn closing_bracket(c: char) -> Option<char> {
+    BRACKETS
+        .iter()
+        .find(|(open, _)| *open == c)
+        .map(|(_, close)| *close)
+}
+
+fn opening_bracket(c: char) -> Option<char> {
+    BRACKETS
+        .iter()
+        .find(|(_, close)| *close == c)
+        .map(|(open, _)| *open)
+}
+
+/// Character closing `c` when it opens a pair, quotes closing themselves.
+fn closing_of(c: char) -> Option<char> {
+    closing_bracket(c).or(QUOTES.contains(&c).then_some(c))
+}
+
+fn is_closing(c: char) -> bool {
+    opening_bracket(c).is_some() || QUOTES.contains(&c)
+}
+
+fn previous_char(buffer: &GapBuffer, index: usize) -> Option<char> {
+    index.checked_sub(1).and_then(|index| buffer.char_at(index))
+}
+
+/// Leading spaces and tabs of the line containing `index`, up to `index`.
+fn indentation(buffer: &GapBuffer, index: usize) -> String {
+    let start = buffer.line_start(index);
+    let mut end = start;
+    while end < index && matches!(buffer.char_at(end), Some(' ' | '\t')) {
+        end += 1;
+    }
+    buffer.slice(start..end)
+}
+
+/// Breaks the line at the cursor, indenting the new line like the current one, and one level
+/// deeper after an opening bracket. Between an empty pair of brackets, the closing one goes on
+/// a line of its own.
+pub fn new_line(buffer: &mut GapBuffer, style: IndentStyle) {
+    let cursor = buffer.cursor();
+    let indentation = indentation(buffer, cursor);
+    let opened = previous_char(buffer, cursor).and_then(closing_bracket);
+    let next = buffer.char_at(cursor);
+    buffer.insert_char('\n');
+    buffer.insert_str(&indentation);
+    if let Some(close) = opened {
+        buffer.insert_str(&style.unit());
+        if next == Some(close) {
+            let position = buffer.cursor();
+            buffer.insert_char('\n');
+            buffer.insert_str(&indentation);
+            buffer.move_gap(position);
+        }
+    }
+}
+
+/// Types `c`, closing brackets and quotes as they are opened and stepping over the closing
+/// character that is already there.
+pub fn insert_char(buffer: &mut GapBuffer, c: char, style: IndentStyle) {
+    let cursor = buffer.cursor();
+    let next = buffer.char_at(cursor);
+    if is_closing(c) && next == Some(c) {
+        buffer.move_gap(cursor + 1);
+        return;
+    }
+    // pairs are only closed before blanks or closing characters, so that text isn't wrapped
+    // by accident, and quotes not right after a word, as in "don't"
+    let before_blank = next.is_none_or(|next| next.is_whitespace() || is_closing(next));
+    let after_word = previous_char(buffer, cursor).is_some_and(char::is_alphanumeric);
+    let close = closing_of(c).filter(|_| before_blank && !(QUOTES.contains(&c) && after_word));
+    if opening_bracket(c).is_some()
+        && buffer.line_start(cursor) < cursor
+        && indentation(buffer, cursor).chars().count() == cursor - buffer.line_start(cursor)
+    {
+        // a closing bracket on a blank line goes back to the level of the opening one
+        dedent_line(buffer, style);
+    }
+    buffer.insert_char(c);
+    if let Some(close) = close {
+        buffer.insert_char(close);
+        buffer.move_gap(buffer.cursor() - 1);
+    }
+}
+
+/// Deletes the grapheme before the cursor, and the closing character after it when the pair
+/// is empty.
+pub fn delete_backward(buffer: &mut GapBuffer) {
+    let cursor = buffer.cursor();
+    if let Some(open) = previous_char(buffer, cursor)
+        && closing_of(open).is_some()
+        && closing_of(open) == buffer.char_at(cursor)
+    {
+        buffer.delete_range(cursor - 1..cursor + 1);
+        return;
+    }
+    buffer.delete_backward();
+}
+
+/// Lines touched by the characters in `range`, as the index of their first and last line.
+fn lines_of(buffer: &GapBuffer, range: &Range<usize>) -> (usize, usize) {
+    let first = buffer.line_of(range.start);
+    let mut last = buffer.line_of(range.end);
+    // a selection ending at the start of a line doesn't include it
+    if last > first && buffer.line_start_of(last) == range.end {
+        last -= 1;
+    }
+    (first, last)
+}
+
+/// Range from the start of `first` to the end of `last`.
+fn line_range(buffer: &GapBuffer, first: usize, last: usize) -> Range<usize> {
+    buffer.line_start_of(first)..buffer.line_end(buffer.line_start_of(last))
+}
+
+/// Indents the lines touched by `range` one level, blank lines aside, and returns the range
+/// of the lines afterwards.
+pub fn indent_lines(
+    buffer: &mut GapBuffer,
+    range: Range<usize>,
+    style: IndentStyle,
+) -> Range<usize> {
+    let (first, last) = lines_of(buffer, &range);
+    for line in (first..=last).rev() {
+        let start = buffer.line_start_of(line);
+        if start < buffer.line_end(start) {
+            buffer.move_gap(start);
+            buffer.insert_str(&style.unit());
+        }
+    }
+    line_range(buffer, first, last)
+}
+
+/// Removes one level of indentation from the lines touched by `range`, and returns the range
+/// of the lines afterwards.
+pub fn dedent_lines(
+    buffer: &mut GapBuffer,
+    range: Range<usize>,
+    style: IndentStyle,
+) -> Range<usize> {
+    let (first, last) = lines_of(buffer, &range);
+    for line in (first..=last).rev() {
+        let start = buffer.line_start_of(line);
+        let length = dedent_length(buffer, start, style);
+        buffer.delete_range(start..start + length);
+    }
+    line_range(buffer, first, last)
+}
+
+/// Removes one level of indentation from the line of the cursor, which stays on the same
+/// character.
+pub fn dedent_line(buffer: &mut GapBuffer, style: IndentStyle) {
+    let cursor = buffer.cursor();
+    let start = buffer.line_start(cursor);
+    let length = dedent_length(buffer, start, style);
+    buffer.delete_range(start..start + length);
+    buffer.move_gap(cursor.saturating_sub(length).max(start));
+}
+
+/// Number of characters making up one level of indentation at `line_start`: a tab, or up to
+/// a level of spaces.
+fn dedent_length(buffer: &GapBuffer, line_start: usize, style: IndentStyle) -> usize {
+    if buffer.char_at(line_start) == Some('\t') {
+        return 1;
+    }
+    (line_start..line_start + style.width)
+        .take_while(|&index| buffer.char_at(index) == Some(' '))
+        .count()
+}
+
+/// Index of the bracket matching the one at `index`, if there is one within `bounds`.
+pub fn matching_bracket(buffer: &GapBuffer, index: usize, bounds: Range<usize>) -> Option<usize> {
+    let c = buffer.char_at(index)?;
+    let (open, close, forward) = match (closing_bracket(c), opening_bracket(c)) {
+        (Some(close), _) => (c, close, true),
+        (_, Some(open)) => (open, c, false),
+        _ => return None,
+    };
+    let mut depth = 0usize;
+    let mut position = index;
+    loop {
+        match buffer.char_at(position) {
+            Some(found) if found == open && forward || found == close && !forward => depth += 1,
+            Some(found) if found == close || found == open => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(position);
+                }
+            }
+            _ => {}
+        }
+        position = if forward {
+            position + 1
+        } else {
+            position.checked_sub(1)?
+        };
+        if !bounds.contains(&position) {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACES: IndentStyle = IndentStyle {
+        use_tabs: false,
+        width: 4,
+    };
+
+    fn buffer(text: &str, cursor: usize) -> GapBuffer {
+        let mut buffer = GapBuffer::from_str(text, 8);
+        buffer.move_gap(cursor);
+        buffer
+    }
+
+    fn type_chars(buffer: &mut GapBuffer, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => new_line(buffer, SPACES),
+                c => insert_char(buffer, c, SPACES),
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_line() {
+        let mut gap_buffer = buffer("    let a = 1;", 14);
+        new_line(&mut gap_buffer, SPACES);
+        assert_eq!(gap_buffer.to_string(), "    let a = 1;\n    ");
+
+        let mut gap_buffer = buffer("", 0);
+        type_chars(&mut gap_buffer, "fn main() {\nlet v = [\n1,");
+        assert_eq!(
+            gap_buffer.to_string(),
+            "fn main() {\n    let v = [\n        1,\n    ]\n}"
+        );
+
+        // a closing bracket typed on a blank line goes back one level
+        let mut gap_buffer = buffer("  {\n      ", 10);
+        insert_char(&mut gap_buffer, '}', SPACES);
+        assert_eq!(gap_buffer.to_string(), "  {\n  }");
+
+        let tabs = IndentStyle {
+            use_tabs: true,
+            width: 4,
+        };
+        let mut gap_buffer = buffer("\tif x {}", 7);
+        new_line(&mut gap_buffer, tabs);
+        assert_eq!(gap_buffer.to_string(), "\tif x {\n\t\t\n\t}");
+        assert_eq!(gap_buffer.cursor(), 10);
+    }
+
+    #[test]
+    fn test_pairs() {
+        let mut gap_buffer = buffer("", 0);
+        type_chars(&mut gap_buffer, "f(\"a\", x[0])");
+        assert_eq!(gap_buffer.to_string(), "f(\"a\", x[0])");
+        let mut gap_buffer = buffer("", 0);
+        type_chars(&mut gap_buffer, "don't (");
+        assert_eq!(gap_buffer.to_string(), "don't ()");
+        let mut gap_buffer = buffer("x", 0);
+        type_chars(&mut gap_buffer, "(");
+        assert_eq!(gap_buffer.to_string(), "(x");
+
+        let mut gap_buffer = buffer("a[]", 2);
+        delete_backward(&mut gap_buffer);
+        assert_eq!(gap_buffer.to_string(), "a");
+        delete_backward(&mut gap_buffer);
+        assert_eq!(gap_buffer.to_string(), "");
+    }
+
+    #[test]
+    fn test_indent_lines() {
+        let mut gap_buffer = buffer("a\n\n  b\nc", 0);
+        let lines = indent_lines(&mut gap_buffer, 1..6, SPACES);
+        assert_eq!(gap_buffer.to_string(), "    a\n\n      b\nc");
+        assert_eq!(lines, 0..14);
+        let lines = dedent_lines(&mut gap_buffer, lines, SPACES);
+        assert_eq!(gap_buffer.to_string(), "a\n\n  b\nc");
+        assert_eq!(lines, 0..6);
+        dedent_lines(&mut gap_buffer, 3..7, SPACES);
+        assert_eq!(gap_buffer.to_string(), "a\n\nb\nc");
+
+        let mut gap_buffer = buffer("\t\tx", 3);
+        dedent_line(&mut gap_buffer, SPACES);
+        assert_eq!(
+            (gap_buffer.to_string().as_str(), gap_buffer.cursor()),
+            ("\tx", 2)
+        );
+    }
+
+    #[test]
+    fn test_matching_bracket() {
+        let gap_buffer = buffer("f(a[0], (b)) }", 0);
+        let all = 0..gap_buffer.len();
+        assert_eq!(matching_bracket(&gap_buffer, 1, all.clone()), Some(11));
+        assert_eq!(matching_bracket(&gap_buffer, 11, all.clone()), Some(1));
+        assert_eq!(matching_bracket(&gap_buffer, 6, all.clone()), None);
+        assert_eq!(matching_bracket(&gap_buffer, 3, all.clone()), Some(5));
+        assert_eq!(matching_bracket(&gap_buffer, 13, all), None);
+        // the search stops at the bounds
+        assert_eq!(matching_bracket(&gap_buffer, 1, 0..8), None);
+    }
+}
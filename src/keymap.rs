@@ -0,0 +1,753 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, mem,
+};
+
+use crate::app::AppMode;
+
+/// Everything a key can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Back,
+    Quit,
+    EditMode,
+    SelectMode,
+    SearchMode,
+    OpenThemePicker,
+    SelectNext,
+    SelectPrevious,
+    AddSnippet,
+    ApplySearch,
+    Submit,
+    NextInput,
+    Confirm,
+    NewLine,
+    Indent,
+    Dedent,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    DeleteLine,
+    CursorLeft,
+    CursorRight,
+    CursorWordLeft,
+    CursorWordRight,
+    CursorLineStart,
+    CursorLineEnd,
+    KillToLineStart,
+    KillToLineEnd,
+    KillWordBackward,
+    Yank,
+    CursorUp,
+    CursorDown,
+    ExtendSelectionLeft,
+    ExtendSelectionRight,
+    ExtendSelectionUp,
+    ExtendSelectionDown,
+    Cut,
+    Copy,
+    Paste,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    SplitHorizontal,
+    SplitVertical,
+    ClosePane,
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+    OpenExternalEditor,
+    Undo,
+    Redo,
+    Find,
+    ToggleRegex,
+    ToggleCase,
+    ReplaceAll,
+    ToggleWrap,
+}
+
+impl Action {
+    const ALL: [Action; 59] = [
+        Action::Back,
+        Action::Quit,
+        Action::EditMode,
+        Action::SelectMode,
+        Action::SearchMode,
+        Action::OpenThemePicker,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::AddSnippet,
+        Action::ApplySearch,
+        Action::Submit,
+        Action::NextInput,
+        Action::Confirm,
+        Action::NewLine,
+        Action::Indent,
+        Action::Dedent,
+        Action::DeleteBackward,
+        Action::DeleteForward,
+        Action::DeleteWordBackward,
+        Action::DeleteWordForward,
+        Action::DeleteLine,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorWordLeft,
+        Action::CursorWordRight,
+        Action::CursorLineStart,
+        Action::CursorLineEnd,
+        Action::KillToLineStart,
+        Action::KillToLineEnd,
+        Action::KillWordBackward,
+        Action::Yank,
+        Action::CursorUp,
+        Action::CursorDown,
+        Action::ExtendSelectionLeft,
+        Action::ExtendSelectionRight,
+        Action::ExtendSelectionUp,
+        Action::ExtendSelectionDown,
+        Action::Cut,
+        Action::Copy,
+        Action::Paste,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::SplitHorizontal,
+        Action::SplitVertical,
+        Action::ClosePane,
+        Action::FocusLeft,
+        Action::FocusRight,
+        Action::FocusUp,
+        Action::FocusDown,
+        Action::OpenExternalEditor,
+        Action::Undo,
+        Action::Redo,
+        Action::Find,
+        Action::ToggleRegex,
+        Action::ToggleCase,
+        Action::ReplaceAll,
+        Action::ToggleWrap,
+    ];
+
+    /// Name of the action in the `[keys]` tables of the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Back => "back",
+            Action::Quit => "quit",
+            Action::EditMode => "edit_mode",
+            Action::SelectMode => "select_mode",
+            Action::SearchMode => "search_mode",
+            Action::OpenThemePicker => "open_theme_picker",
+            Action::SelectNext => "select_next",
+            Action::SelectPrevious => "select_previous",
+            Action::AddSnippet => "add_snippet",
+            Action::ApplySearch => "apply_search",
+            Action::Submit => "submit",
+            Action::NextInput => "next_input",
+            Action::Confirm => "confirm",
+            Action::NewLine => "new_line",
+            Action::Indent => "indent",
+            Action::Dedent => "dedent",
+            Action::DeleteBackward => "delete_backward",
+            Action::DeleteForward => "delete_forward",
+            Action::DeleteWordBackward => "delete_word_backward",
+            Action::DeleteWordForward => "delete_word_forward",
+            Action::DeleteLine => "delete_line",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorWordLeft => "cursor_word_left",
+            Action::CursorWordRight => "cursor_word_right",
+            Action::CursorLineStart => "cursor_line_start",
+            Action::CursorLineEnd => "cursor_line_end",
+            Action::KillToLineStart => "kill_to_line_start",
+            Action::KillToLineEnd => "kill_to_line_end",
+            Action::KillWordBackward => "kill_word_backward",
+            Action::Yank => "yank",
+            Action::CursorUp => "cursor_up",
+            Action::CursorDown => "cursor_down",
+            Action::ExtendSelectionLeft => "extend_selection_left",
+            Action::ExtendSelectionRight => "extend_selection_right",
+            Action::ExtendSelectionUp => "extend_selection_up",
+            Action::ExtendSelectionDown => "extend_selection_down",
+            Action::Cut => "cut",
+            Action::Copy => "copy",
+            Action::Paste => "paste",
+            Action::NewTab => "new_tab",
+            Action::CloseTab => "close_tab",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::SplitHorizontal => "split_horizontal",
+            Action::SplitVertical => "split_vertical",
+            Action::ClosePane => "close_pane",
+            Action::FocusLeft => "focus_left",
+            Action::FocusRight => "focus_right",
+            Action::FocusUp => "focus_up",
+            Action::FocusDown => "focus_down",
+            Action::OpenExternalEditor => "open_external_editor",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Find => "find",
+            Action::ToggleRegex => "toggle_regex",
+            Action::ToggleCase => "toggle_case",
+            Action::ReplaceAll => "replace_all",
+            Action::ToggleWrap => "toggle_wrap",
+        }
+    }
+
+    /// Whether the action arranges the editor's tabs and panes, which works in vim mode too.
+    pub fn is_layout(self) -> bool {
+        matches!(
+            self,
+            Action::NewTab
+                | Action::CloseTab
+                | Action::NextTab
+                | Action::PreviousTab
+                | Action::SplitHorizontal
+                | Action::SplitVertical
+                | Action::ClosePane
+                | Action::FocusLeft
+                | Action::FocusRight
+                | Action::FocusUp
+                | Action::FocusDown
+        )
+    }
+
+    fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A single key press along with its modifiers.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // shift is already part of upper case characters and of BackTab.
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyChord { code, modifiers }
+    }
+
+    /// Parses keys such as `q`, `A`, `enter`, `ctrl-s`, `alt-f` or `shift-tab`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(stripped) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("shift-") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+        let code = match rest {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('f'), Some(_)) => KeyCode::F(rest[1..].parse().ok()?),
+                    _ => return None,
+                }
+            }
+        };
+        Some(KeyChord::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(key: &KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::BackTab => f.write_str("shift-tab"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            code => f.write_str(&code.to_string().to_lowercase()),
+        }
+    }
+}
+
+/// Maps action names to the keys bound to them.
+type BindingTable = BTreeMap<String, Vec<String>>;
+
+/// Set of default bindings the `[keys]` tables are applied on top of.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyProfile {
+    /// Arrow keys, home and end.
+    #[default]
+    Default,
+    /// Adds the emacs/readline keys to the editor and the input fields.
+    Readline,
+}
+
+/// Key bindings from the `[keys]` section of the config file.
+///
+/// Each table maps an action name to the keys that trigger it. A sequence of keys is written
+/// with spaces, for example `"g g"`. Bindings given here replace the default bindings of the
+/// same action in the same mode.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub profile: KeyProfile,
+    /// Bindings available in every mode.
+    pub global: BindingTable,
+    pub command: BindingTable,
+    pub select: BindingTable,
+    pub search: BindingTable,
+    pub edit: BindingTable,
+    pub popup: BindingTable,
+    pub theme_picker: BindingTable,
+}
+
+impl KeysConfig {
+    pub fn defaults(profile: KeyProfile) -> Self {
+        fn table(bindings: &[(Action, &[&str])]) -> BindingTable {
+            bindings
+                .iter()
+                .map(|(action, keys)| {
+                    let keys = keys.iter().map(|key| key.to_string()).collect();
+                    (action.name().to_string(), keys)
+                })
+                .collect()
+        }
+        let text_editing: &[(Action, &[&str])] = &[
+            (Action::CursorLeft, &["left"]),
+            (Action::CursorRight, &["right"]),
+            (Action::CursorUp, &["up"]),
+            (Action::CursorDown, &["down"]),
+            (Action::CursorLineStart, &["home"]),
+            (Action::CursorLineEnd, &["end"]),
+            (Action::DeleteForward, &["delete"]),
+            // terminals mostly send ctrl-backspace as ctrl-h
+            (
+                Action::DeleteWordBackward,
+                &["ctrl-backspace", "ctrl-h", "alt-backspace"],
+            ),
+            (Action::DeleteWordForward, &["ctrl-delete"]),
+        ];
+        let mut defaults = KeysConfig {
+            profile,
+            global: table(&[(Action::Back, &["esc"])]),
+            command: table(&[
+                (Action::Quit, &["q"]),
+                (Action::EditMode, &["e"]),
+                (Action::SelectMode, &["s"]),
+                (Action::SearchMode, &["/"]),
+                (Action::OpenThemePicker, &["t"]),
+                (Action::OpenExternalEditor, &["o"]),
+            ]),
+            select: table(&[
+                (Action::SelectNext, &["j", "down"]),
+                (Action::SelectPrevious, &["k", "up"]),
+                (Action::AddSnippet, &["a"]),
+                (Action::OpenExternalEditor, &["o"]),
+            ]),
+            search: table(&[
+                (Action::ApplySearch, &["enter"]),
+                (Action::DeleteBackward, &["backspace"]),
+            ]),
+            edit: table(&[
+                (Action::NewLine, &["enter"]),
+                (Action::Indent, &["tab"]),
+                (Action::Dedent, &["shift-tab"]),
+                (Action::DeleteBackward, &["backspace"]),
+                (Action::DeleteLine, &["shift-delete"]),
+                (Action::ExtendSelectionLeft, &["shift-left"]),
+                (Action::ExtendSelectionRight, &["shift-right"]),
+                (Action::ExtendSelectionUp, &["shift-up"]),
+                (Action::ExtendSelectionDown, &["shift-down"]),
+                (Action::Cut, &["ctrl-x"]),
+                (Action::Copy, &["ctrl-c"]),
+                (Action::Paste, &["ctrl-v"]),
+                (Action::NewTab, &["ctrl-t"]),
+                (Action::CloseTab, &["alt-w"]),
+                (Action::NextTab, &["ctrl-pagedown"]),
+                (Action::PreviousTab, &["ctrl-pageup"]),
+                (Action::SplitHorizontal, &["alt-s"]),
+                (Action::SplitVertical, &["alt-v"]),
+                (Action::ClosePane, &["alt-q"]),
+                (Action::FocusLeft, &["alt-left"]),
+                (Action::FocusRight, &["alt-right"]),
+                (Action::FocusUp, &["alt-up"]),
+                (Action::FocusDown, &["alt-down"]),
+                (Action::OpenExternalEditor, &["ctrl-o"]),
+                (Action::Undo, &["ctrl-z"]),
+                (Action::Redo, &["ctrl-r"]),
+                (Action::ToggleRegex, &["alt-r"]),
+                (Action::ToggleCase, &["alt-c"]),
+                (Action::ReplaceAll, &["alt-a"]),
+                (Action::ToggleWrap, &["alt-z"]),
+            ]),
+            popup: table(&[
+                (Action::Submit, &["ctrl-s"]),
+                (Action::NextInput, &["enter"]),
+                (Action::DeleteBackward, &["backspace"]),
+            ]),
+            theme_picker: table(&[
+                (Action::SelectNext, &["j", "down"]),
+                (Action::SelectPrevious, &["k", "up"]),
+                (Action::Confirm, &["enter"]),
+            ]),
+        };
+        let readline: &[(Action, &[&str])] = &[
+            (Action::CursorLeft, &["ctrl-b"]),
+            (Action::CursorRight, &["ctrl-f"]),
+            (Action::CursorUp, &["ctrl-p"]),
+            (Action::CursorDown, &["ctrl-n"]),
+            (Action::CursorWordLeft, &["alt-b"]),
+            (Action::CursorWordRight, &["alt-f"]),
+            (Action::CursorLineStart, &["ctrl-a"]),
+            (Action::CursorLineEnd, &["ctrl-e"]),
+            (Action::KillToLineStart, &["ctrl-u"]),
+            (Action::KillToLineEnd, &["ctrl-k"]),
+            (Action::KillWordBackward, &["ctrl-w"]),
+            (Action::Yank, &["ctrl-y"]),
+            (Action::DeleteForward, &["ctrl-d"]),
+            (Action::DeleteWordForward, &["alt-d"]),
+        ];
+        // ctrl-f moves the cursor in the readline profile
+        let find = match profile {
+            KeyProfile::Default => "ctrl-f",
+            KeyProfile::Readline => "ctrl-s",
+        };
+        defaults
+            .edit
+            .insert(Action::Find.name().to_string(), vec![find.to_string()]);
+        // every gap buffer backed input shares the text editing keys.
+        for input in [
+            &mut defaults.search,
+            &mut defaults.edit,
+            &mut defaults.popup,
+        ] {
+            let mut bindings = text_editing.to_vec();
+            if profile == KeyProfile::Readline {
+                bindings.extend_from_slice(readline);
+            }
+            for (action, keys) in bindings {
+                input
+                    .entry(action.name().to_string())
+                    .or_default()
+                    .extend(keys.iter().map(|key| key.to_string()));
+            }
+        }
+        defaults
+    }
+
+    fn tables(&self) -> [(Option<AppMode>, &BindingTable); 7] {
+        [
+            (None, &self.global),
+            (Some(AppMode::Command), &self.command),
+            (Some(AppMode::Select), &self.select),
+            (Some(AppMode::Search), &self.search),
+            (Some(AppMode::Edit), &self.edit),
+            (Some(AppMode::Popup), &self.popup),
+            (Some(AppMode::ThemePicker), &self.theme_picker),
+        ]
+    }
+}
+
+type Bindings = HashMap<Vec<KeyChord>, Action>;
+
+/// Outcome of looking up the keys pressed so far.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Action(Action),
+    /// The keys are the beginning of a longer binding.
+    Pending,
+    Unbound,
+}
+
+pub struct Keymap {
+    global: Bindings,
+    modes: HashMap<AppMode, Bindings>,
+}
+
+impl Keymap {
+    /// Builds the keymap from the default bindings overridden by `config`.
+    pub fn new(config: &KeysConfig) -> Result<Self, Vec<String>> {
+        let mut keymap = Keymap {
+            global: HashMap::new(),
+            modes: HashMap::new(),
+        };
+        let mut errors = Vec::new();
+        let defaults = KeysConfig::defaults(config.profile);
+        for ((mode, default_table), (_, table)) in
+            defaults.tables().into_iter().zip(config.tables())
+        {
+            let section = mode.map_or(String::from("global"), |mode| mode.config_name());
+            // user bindings are inserted last so that they win over conflicting defaults.
+            let merged = default_table
+                .iter()
+                .filter(|(name, _)| !table.contains_key(*name))
+                .chain(table.iter());
+            let bindings = match mode {
+                Some(mode) => keymap.modes.entry(mode).or_default(),
+                None => &mut keymap.global,
+            };
+            for (name, keys) in merged {
+                let Some(action) = Action::from_name(name) else {
+                    errors.push(format!("keys.{}: unknown action {:?}", section, name));
+                    continue;
+                };
+                for key in keys {
+                    let key = key.as_str();
+                    let sequence: Option<Vec<KeyChord>> =
+                        key.split_whitespace().map(KeyChord::parse).collect();
+                    match sequence {
+                        Some(sequence) if !sequence.is_empty() => {
+                            bindings.insert(sequence, action);
+                        }
+                        _ => {
+                            errors.push(format!("keys.{}.{}: invalid key {:?}", section, name, key))
+                        }
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Looks up the keys pressed so far, mode bindings taking precedence over global ones.
+    pub fn resolve(&self, mode: &AppMode, keys: &[KeyChord]) -> Resolution {
+        let tables = [self.modes.get(mode), Some(&self.global)];
+        for bindings in tables.iter().flatten() {
+            if let Some(action) = bindings.get(keys) {
+                return Resolution::Action(*action);
+            }
+        }
+        let is_prefix = tables.iter().flatten().any(|bindings| {
+            bindings
+                .keys()
+                .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
+        });
+        if is_prefix {
+            Resolution::Pending
+        } else {
+            Resolution::Unbound
+        }
+    }
+
+    /// Describes the first key bound to `action`, for the help texts.
+    pub fn label(&self, mode: &AppMode, action: Action) -> String {
+        let tables = [self.modes.get(mode), Some(&self.global)];
+        tables
+            .iter()
+            .flatten()
+            .flat_map(|bindings| bindings.iter())
+            .filter(|(_, bound)| **bound == action)
+            .map(|(sequence, _)| {
+                sequence
+                    .iter()
+                    .map(|chord| chord.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .min_by_key(|label| (label.len(), label.clone()))
+            .unwrap_or_else(|| String::from("unbound"))
+    }
+}
+
+/// Keys pressed so far of a sequence that may still be bound.
+#[derive(Default)]
+pub struct PendingKeys {
+    keys: Vec<KeyEvent>,
+}
+
+impl PendingKeys {
+    /// Adds `key` to the sequence. Returns the keys to insert as text, then what the keys left
+    /// pending resolve to.
+    pub fn push(
+        &mut self,
+        keymap: &Keymap,
+        mode: &AppMode,
+        key: KeyEvent,
+    ) -> (Vec<KeyEvent>, Resolution) {
+        self.keys.push(key);
+        let sequence: Vec<KeyChord> = self.keys.iter().map(KeyChord::from).collect();
+        match keymap.resolve(mode, &sequence) {
+            Resolution::Action(action) => {
+                self.keys.clear();
+                (Vec::new(), Resolution::Action(action))
+            }
+            Resolution::Pending => (Vec::new(), Resolution::Pending),
+            Resolution::Unbound if self.keys.len() == 1 => {
+                self.keys.clear();
+                let typed = Some(key).filter(is_text_input).into_iter().collect();
+                (typed, Resolution::Unbound)
+            }
+            Resolution::Unbound => {
+                // the keys of the abandoned sequence are typed, and the last one may start
+                // a sequence of its own
+                let mut typed = mem::take(&mut self.keys);
+                typed.pop();
+                typed.retain(is_text_input);
+                let (rest, resolution) = self.push(keymap, mode, key);
+                typed.extend(rest);
+                (typed, resolution)
+            }
+        }
+    }
+}
+
+/// Whether a key that isn't bound to an action should be inserted as text.
+pub fn is_text_input(key: &KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Char(_)) && (key.modifiers - KeyModifiers::SHIFT).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(text: &str) -> Vec<KeyChord> {
+        text.split_whitespace()
+            .map(|key| KeyChord::parse(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_key_chord() {
+        assert_eq!(
+            KeyChord::parse("ctrl-s"),
+            Some(KeyChord::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            KeyChord::parse("shift-tab"),
+            Some(KeyChord::new(KeyCode::BackTab, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyChord::parse("A"),
+            Some(KeyChord::new(KeyCode::Char('A'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(KeyChord::parse("ctrl-"), None);
+        assert_eq!(KeyChord::parse("ctrl-s").unwrap().to_string(), "ctrl-s");
+    }
+
+    #[test]
+    fn test_resolve_sequence() {
+        let mut config = KeysConfig::default();
+        config
+            .select
+            .insert(String::from("select_next"), vec![String::from("g g")]);
+        let keymap = Keymap::new(&config).unwrap();
+        assert!(matches!(
+            keymap.resolve(&AppMode::Select, &keys("g")),
+            Resolution::Pending
+        ));
+        assert!(matches!(
+            keymap.resolve(&AppMode::Select, &keys("g g")),
+            Resolution::Action(Action::SelectNext)
+        ));
+        // the override replaces the default binding of the action
+        assert!(matches!(
+            keymap.resolve(&AppMode::Select, &keys("j")),
+            Resolution::Unbound
+        ));
+        assert!(matches!(
+            keymap.resolve(&AppMode::Select, &keys("esc")),
+            Resolution::Action(Action::Back)
+        ));
+    }
+
+    #[test]
+    fn test_pending_keys() {
+        let mut config = KeysConfig::default();
+        config
+            .edit
+            .insert(String::from("undo"), vec![String::from("g g")]);
+        let keymap = Keymap::new(&config).unwrap();
+        let mut pending = PendingKeys::default();
+        let mut push = |key: &str| {
+            let chord = KeyChord::parse(key).unwrap();
+            let (typed, resolution) = pending.push(
+                &keymap,
+                &AppMode::Edit,
+                KeyEvent::new(chord.code, chord.modifiers),
+            );
+            let typed: Vec<KeyChord> = typed.iter().map(KeyChord::from).collect();
+            (typed, resolution)
+        };
+        assert_eq!(push("g"), (Vec::new(), Resolution::Pending));
+        assert_eq!(push("g"), (Vec::new(), Resolution::Action(Action::Undo)));
+        assert_eq!(push("x"), (keys("x"), Resolution::Unbound));
+        // the keys of an unfinished sequence are typed rather than lost
+        push("g");
+        assert_eq!(push("o"), (keys("g o"), Resolution::Unbound));
+        push("g");
+        assert_eq!(
+            push("enter"),
+            (keys("g"), Resolution::Action(Action::NewLine))
+        );
+        push("g");
+        assert_eq!(push("g"), (Vec::new(), Resolution::Action(Action::Undo)));
+    }
+
+    #[test]
+    fn test_invalid_bindings() {
+        let mut config = KeysConfig::default();
+        config
+            .edit
+            .insert(String::from("fly"), vec![String::from("f")]);
+        config
+            .edit
+            .insert(String::from("indent"), vec![String::from("ctrl-tabby")]);
+        assert_eq!(
+            Keymap::new(&config).err().map(|errors| errors.len()),
+            Some(2)
+        );
+    }
+}
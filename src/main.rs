@@ -1,28 +1,88 @@
 mod app;
+mod cli;
+mod clipboard;
+mod config;
 mod editor;
+mod events;
+mod external;
+mod find;
+mod highlight;
+mod indent;
+mod keymap;
+mod panes;
 mod persistence;
+mod readline;
+mod search;
+mod theme;
 mod view;
+mod vim;
 
-use log::error;
-use simplelog::{Config, WriteLogger};
-use std::fs::File;
+use anyhow::{Context, Result};
+use clap::Parser;
+use log::LevelFilter;
+use simplelog::WriteLogger;
+use std::{fs, fs::File, path::Path};
 
 use crate::app::App;
-fn main() {
-    setup_logger();
-    let mut terminal = ratatui::init();
-    let mut app = App::new();
-    let _ = app.run(&mut terminal);
-    ratatui::restore();
-}
+use crate::cli::{Cli, Command};
+use crate::config::{Config, Paths};
+use crate::theme::UiTheme;
 
-fn setup_logger() {
-    let log_file = File::create("dial.log").unwrap();
-    let result = WriteLogger::init(log::LevelFilter::Debug, Config::default(), log_file);
-    match result {
-        Ok(()) => {}
-        Err(_) => {
-            error!("There was an error initializing the logger")
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if let Some(Command::Config { print_default }) = &cli.command {
+        if *print_default {
+            print!("{}", Config::print_default()?);
+        } else {
+            println!("{}", config::config_file(&cli)?.display());
         }
+        return Ok(());
+    }
+    let config_file = config::config_file(&cli)?;
+    let config = Config::load(&config_file)?;
+    let paths = Paths::resolve(&cli, &config, config_file)?;
+    setup_logger(&paths.log_file, config.log_level)?;
+    if let Some(Command::Edit { id }) = &cli.command {
+        return edit_snippet(&paths.data_dir, *id);
+    }
+    let theme_set = highlight::load_theme_set(&paths.themes_dir())?;
+    config.validate_theme(&theme_set)?;
+    let syntax_set = highlight::load_syntax_set(&paths.syntaxes_dir(), &paths.cache_dir)?;
+    let ui_theme = UiTheme::load(&config.ui, paths.config_dir())?;
+    let mut terminal = external::init_terminal();
+    let mut app = App::new(config, paths, syntax_set, theme_set, ui_theme);
+    let result = app.run(&mut terminal);
+    external::restore_terminal();
+    result
+}
+
+/// Edits the snippet at position `id` in $VISUAL or $EDITOR without starting the interface.
+fn edit_snippet(data_dir: &Path, id: usize) -> Result<()> {
+    let mut snippets = persistence::load_snippets(data_dir)?;
+    let count = snippets.len();
+    let snippet = id
+        .checked_sub(1)
+        .and_then(|index| snippets.get_mut(index))
+        .with_context(|| format!("there is no snippet {}, the list has {}", id, count))?;
+    let code = external::edit(
+        &external::editor_command()?,
+        &snippet.code,
+        &snippet.language,
+    )?;
+    if code != snippet.code {
+        snippet.code = code;
+        persistence::save_snippets(data_dir, &snippets)?;
+    }
+    Ok(())
+}
+
+fn setup_logger(path: &Path, level: LevelFilter) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("could not create directory {:?}", parent))?;
     }
+    let log_file =
+        File::create(path).with_context(|| format!("could not create log file {:?}", path))?;
+    WriteLogger::init(level, simplelog::Config::default(), log_file)
+        .with_context(|| "could not initialize the logger")
 }
@@ -0,0 +1,329 @@
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+use std::collections::HashMap;
+
+/// Cursor, selection and scroll of a snippet in a pane.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct View {
+    pub cursor: usize,
+    /// Other end of the selection, the cursor being the end that moves.
+    pub selection_anchor: Option<usize>,
+    /// Index of the first visible line.
+    pub scroll: usize,
+}
+
+/// Editor pane, showing the active one of its tabs.
+pub struct Pane {
+    /// Snippets open in the tabs, by index in the full list.
+    pub tabs: Vec<usize>,
+    pub active: usize,
+    /// Area of the pane in the last frame.
+    pub area: Rect,
+    /// Views of the snippets shown in the pane by index in the full list, as they were when
+    /// the pane last had the focus.
+    pub views: HashMap<usize, View>,
+}
+
+impl Pane {
+    fn new(snippet: usize) -> Self {
+        Pane {
+            tabs: vec![snippet],
+            active: 0,
+            area: Rect::default(),
+            views: HashMap::new(),
+        }
+    }
+
+    /// View of the snippet of the active tab, if the pane showed it before.
+    pub fn view(&self) -> Option<View> {
+        self.views.get(&self.snippet()).copied()
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.views.insert(self.snippet(), view);
+    }
+
+    /// Snippet of the active tab.
+    pub fn snippet(&self) -> usize {
+        self.tabs[self.active]
+    }
+
+    /// Shows `snippet` in the active tab, or switches to its tab if it has one.
+    pub fn open(&mut self, snippet: usize) {
+        match self.tabs.iter().position(|&tab| tab == snippet) {
+            Some(position) => self.active = position,
+            None => self.tabs[self.active] = snippet,
+        }
+    }
+
+    /// Opens a tab next to the active one, showing the same snippet until another is selected.
+    pub fn new_tab(&mut self) {
+        self.tabs.insert(self.active + 1, self.snippet());
+        self.active += 1;
+    }
+
+    /// Closes the active tab unless it is the last one.
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() > 1 {
+            self.tabs.remove(self.active);
+            self.active = self.active.min(self.tabs.len() - 1);
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.active = (self.active + 1) % self.tabs.len();
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+    }
+}
+
+/// Side of the focused pane to move the focus to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// panes are split in two halves, each of which can be split again.
+enum Node {
+    Pane(usize),
+    Split(Direction, Box<[Node; 2]>),
+}
+
+/// Panes of the editor with the one that has the focus.
+pub struct Panes {
+    panes: Vec<Pane>,
+    root: Node,
+    focused: usize,
+}
+
+impl Panes {
+    pub fn new(snippet: usize) -> Self {
+        Panes {
+            panes: vec![Pane::new(snippet)],
+            root: Node::Pane(0),
+            focused: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pane> {
+        self.panes.iter()
+    }
+
+    pub fn focused(&self) -> &Pane {
+        &self.panes[self.focused]
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Pane {
+        &mut self.panes[self.focused]
+    }
+
+    pub fn is_focused(&self, pane: usize) -> bool {
+        self.focused == pane
+    }
+
+    pub fn get(&self, pane: usize) -> &Pane {
+        &self.panes[pane]
+    }
+
+    pub fn get_mut(&mut self, pane: usize) -> &mut Pane {
+        &mut self.panes[pane]
+    }
+
+    /// Whether a tab of any pane shows `snippet`.
+    pub fn contains(&self, snippet: usize) -> bool {
+        self.panes.iter().any(|pane| pane.tabs.contains(&snippet))
+    }
+
+    /// Splits the focused pane in two along `direction`, and focuses the new half which shows
+    /// the same snippet from the same place, then moves on its own.
+    pub fn split(&mut self, direction: Direction) {
+        let pane = self.panes.len();
+        let mut half = Pane::new(self.focused().snippet());
+        half.views = self.focused().views.clone();
+        self.panes.push(half);
+        replace_leaf(&mut self.root, self.focused, &mut |leaf| {
+            Node::Split(direction, Box::new([leaf, Node::Pane(pane)]))
+        });
+        self.focused = pane;
+    }
+
+    /// Closes the focused pane unless it is the last one, the other half of its split taking
+    /// its place.
+    pub fn close(&mut self) {
+        if self.panes.len() == 1 {
+            return;
+        }
+        let closed = self.focused;
+        let root = std::mem::replace(&mut self.root, Node::Pane(0));
+        self.root = remove_leaf(root, closed);
+        self.panes.remove(closed);
+        self.focused = self.focused.min(self.panes.len() - 1);
+    }
+
+    /// Computes the area of every pane within `area`.
+    pub fn layout(&mut self, area: Rect) {
+        layout_node(&self.root, area, &mut self.panes);
+    }
+
+    /// Pane at `position` in the last frame.
+    pub fn pane_at(&self, position: Position) -> Option<usize> {
+        self.panes
+            .iter()
+            .position(|pane| pane.area.contains(position))
+    }
+
+    pub fn focus(&mut self, pane: usize) {
+        if pane < self.panes.len() {
+            self.focused = pane;
+        }
+    }
+
+    /// Focuses the closest pane on `side` of the focused one, if any.
+    pub fn focus_side(&mut self, side: Side) {
+        let current = self.focused().area;
+        let neighbor = self
+            .panes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, pane)| {
+                let area = pane.area;
+                let (distance, overlaps) = match side {
+                    Side::Left => (
+                        current.x.checked_sub(area.right())?,
+                        overlap_y(current, area),
+                    ),
+                    Side::Right => (
+                        area.x.checked_sub(current.right())?,
+                        overlap_y(current, area),
+                    ),
+                    Side::Up => (
+                        current.y.checked_sub(area.bottom())?,
+                        overlap_x(current, area),
+                    ),
+                    Side::Down => (
+                        area.y.checked_sub(current.bottom())?,
+                        overlap_x(current, area),
+                    ),
+                };
+                overlaps.then_some((distance, index))
+            })
+            .min();
+        if let Some((_, pane)) = neighbor {
+            self.focused = pane;
+        }
+    }
+}
+
+fn overlap_x(a: Rect, b: Rect) -> bool {
+    a.x < b.right() && b.x < a.right()
+}
+
+fn overlap_y(a: Rect, b: Rect) -> bool {
+    a.y < b.bottom() && b.y < a.bottom()
+}
+
+fn replace_leaf(node: &mut Node, pane: usize, replace: &mut impl FnMut(Node) -> Node) {
+    match node {
+        Node::Pane(index) if *index == pane => {
+            let leaf = std::mem::replace(node, Node::Pane(pane));
+            *node = replace(leaf);
+        }
+        Node::Pane(_) => {}
+        Node::Split(_, children) => {
+            for child in children.iter_mut() {
+                replace_leaf(child, pane, replace);
+            }
+        }
+    }
+}
+
+/// `node` without the leaf of `pane`, the panes after it being renumbered.
+fn remove_leaf(node: Node, pane: usize) -> Node {
+    match node {
+        Node::Pane(index) => Node::Pane(if index > pane { index - 1 } else { index }),
+        Node::Split(direction, children) => match *children {
+            [Node::Pane(index), other] | [other, Node::Pane(index)] if index == pane => {
+                remove_leaf(other, pane)
+            }
+            [first, second] => Node::Split(
+                direction,
+                Box::new([remove_leaf(first, pane), remove_leaf(second, pane)]),
+            ),
+        },
+    }
+}
+
+fn layout_node(node: &Node, area: Rect, panes: &mut [Pane]) {
+    match node {
+        Node::Pane(index) => panes[*index].area = area,
+        Node::Split(direction, children) => {
+            let halves =
+                Layout::new(*direction, [Constraint::Fill(1), Constraint::Fill(1)]).split(area);
+            for (child, half) in children.iter().zip(halves.iter()) {
+                layout_node(child, *half, panes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tabs() {
+        let mut pane = Pane::new(3);
+        pane.new_tab();
+        pane.open(5);
+        assert_eq!((pane.tabs.clone(), pane.active), (vec![3, 5], 1));
+        pane.open(3);
+        assert_eq!(pane.snippet(), 3);
+        pane.previous_tab();
+        assert_eq!(pane.snippet(), 5);
+        pane.close_tab();
+        pane.close_tab();
+        assert_eq!(pane.tabs, vec![3]);
+    }
+
+    #[test]
+    fn test_splits() {
+        let mut panes = Panes::new(0);
+        panes.split(Direction::Horizontal);
+        panes.focused_mut().open(1);
+        panes.split(Direction::Vertical);
+        panes.focused_mut().open(2);
+        panes.layout(Rect::new(0, 0, 80, 20));
+        let areas: Vec<Rect> = panes.iter().map(|pane| pane.area).collect();
+        assert_eq!(
+            areas,
+            vec![
+                Rect::new(0, 0, 40, 20),
+                Rect::new(40, 0, 40, 10),
+                Rect::new(40, 10, 40, 10),
+            ]
+        );
+
+        panes.focus_side(Side::Up);
+        assert_eq!(panes.focused().snippet(), 1);
+        panes.focus_side(Side::Up);
+        assert_eq!(panes.focused().snippet(), 1);
+        panes.focus_side(Side::Left);
+        assert_eq!(panes.focused().snippet(), 0);
+        panes.focus_side(Side::Right);
+        assert_eq!(panes.focused().snippet(), 1);
+
+        panes.close();
+        panes.layout(Rect::new(0, 0, 80, 20));
+        let snippets: Vec<usize> = panes.iter().map(|pane| pane.snippet()).collect();
+        assert_eq!(snippets, vec![0, 2]);
+        assert_eq!(panes.get(1).area, Rect::new(40, 0, 40, 20));
+    }
+}
@@ -1,30 +1,27 @@
-use anyhow::{Context, Result, bail};
-use directories::ProjectDirs;
+use anyhow::{Context, Result};
 use log::info;
-use std::{fs, io::Write, path::PathBuf};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use crate::app::Snippet;
 
-fn get_path() -> Result<PathBuf> {
-    let optional_project_dirs = ProjectDirs::from("com", "mouhamadalmounayar", "dial");
-    match optional_project_dirs {
-        Some(project_dirs) => {
-            let data_dir = project_dirs.data_dir();
-            fs::create_dir_all(data_dir)?;
-            Ok(data_dir.join("snippets.json"))
-        }
-        None => bail!("could not get the path to the data directory"),
-    }
+fn get_path(data_dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(data_dir)
+        .with_context(|| format!("could not create directory {:?}", data_dir))?;
+    Ok(data_dir.join("snippets.json"))
 }
 
-pub fn save_snippets(snippets: &[Snippet]) -> Result<()> {
-    let path = get_path()?;
+pub fn save_snippets(data_dir: &Path, snippets: &[Snippet]) -> Result<()> {
+    let path = get_path(data_dir)?;
 
     let mut file =
         fs::File::create(&path).with_context(|| format!("could not create file {:?}", &path))?;
 
     let json_string = serde_json::to_string_pretty(snippets)
-        .with_context(|| format!("could not serialize json string"))?;
+        .with_context(|| "could not serialize json string")?;
 
     file.write_all(json_string.as_bytes())
         .with_context(|| format!("could not write to file {:?}", &path))?;
@@ -33,13 +30,13 @@ pub fn save_snippets(snippets: &[Snippet]) -> Result<()> {
     Ok(())
 }
 
-pub fn load_snippets() -> Result<Vec<Snippet>> {
+pub fn load_snippets(data_dir: &Path) -> Result<Vec<Snippet>> {
     let default_snippets = vec![Snippet {
         language: String::from("txt"),
         title: String::from("Welcome to Dial"),
         code: String::from("Dial is a code snippet manager built with rust and ratatui."),
     }];
-    let path = get_path()?;
+    let path = get_path(data_dir)?;
     if !path.exists() {
         info!("{:?} does not exist", path);
         return Ok(default_snippets);
@@ -52,8 +49,8 @@ pub fn load_snippets() -> Result<Vec<Snippet>> {
         return Ok(default_snippets);
     }
 
-    let snippets = serde_json::from_str(&json_data)
-        .with_context(|| format!("could not serialize json data"))?;
+    let snippets =
+        serde_json::from_str(&json_data).with_context(|| "could not serialize json data")?;
 
     Ok(snippets)
 }
@@ -0,0 +1,168 @@
+use crate::editor::{GapBuffer, column_index, display_width};
+use crate::keymap::Action;
+
+// number of kills remembered by the kill ring.
+const KILL_RING_SIZE: usize = 32;
+
+/// Text removed by the kill actions, shared by every input so that it can be yanked elsewhere.
+#[derive(Default)]
+pub struct KillRing {
+    entries: Vec<String>,
+}
+
+impl KillRing {
+    pub fn push(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        if self.entries.len() == KILL_RING_SIZE {
+            self.entries.remove(0);
+        }
+        self.entries.push(text);
+    }
+
+    /// Most recent kill.
+    pub fn latest(&self) -> Option<&str> {
+        self.entries.last().map(String::as_str)
+    }
+}
+
+/// Applies the cursor movement, deletion, kill and yank actions to `buffer`.
+///
+/// Returns false when `action` isn't one of them, so that the input can handle it itself.
+pub fn handle_action(action: Action, buffer: &mut GapBuffer, kill_ring: &mut KillRing) -> bool {
+    let cursor = buffer.cursor();
+    match action {
+        Action::CursorLeft => buffer.move_gap(buffer.previous_boundary(cursor)),
+        Action::CursorRight => buffer.move_gap(buffer.next_boundary(cursor)),
+        Action::CursorUp => {
+            let line_start = buffer.line_start(cursor);
+            if line_start > 0 {
+                let previous_start = buffer.line_start(line_start - 1);
+                buffer.move_gap(same_column(buffer, cursor, previous_start));
+            }
+        }
+        Action::CursorDown => {
+            let line_end = buffer.line_end(cursor);
+            if line_end < buffer.len() {
+                buffer.move_gap(same_column(buffer, cursor, line_end + 1));
+            }
+        }
+        Action::CursorWordLeft => buffer.move_gap(buffer.word_start(cursor)),
+        Action::CursorWordRight => buffer.move_gap(buffer.word_end(cursor)),
+        Action::CursorLineStart => buffer.move_gap(buffer.line_start(cursor)),
+        Action::CursorLineEnd => buffer.move_gap(buffer.line_end(cursor)),
+        Action::DeleteForward => buffer.delete_forward(),
+        Action::DeleteWordBackward => {
+            buffer.delete_word_backward();
+        }
+        Action::DeleteWordForward => {
+            buffer.delete_word_forward();
+        }
+        Action::DeleteLine => {
+            buffer.delete_line(cursor);
+        }
+        Action::KillToLineStart => {
+            kill_ring.push(buffer.delete_range(buffer.line_start(cursor)..cursor));
+        }
+        Action::KillToLineEnd => {
+            let mut end = buffer.line_end(cursor);
+            // at the end of a line the newline itself is killed.
+            if end == cursor && end < buffer.len() {
+                end += 1;
+            }
+            kill_ring.push(buffer.delete_range(cursor..end));
+        }
+        Action::KillWordBackward => {
+            let mut start = cursor;
+            while start > 0 && buffer.char_at(start - 1).is_some_and(char::is_whitespace) {
+                start -= 1;
+            }
+            while start > 0
+                && buffer
+                    .char_at(start - 1)
+                    .is_some_and(|c| !c.is_whitespace())
+            {
+                start -= 1;
+            }
+            kill_ring.push(buffer.delete_range(start..cursor));
+        }
+        Action::Yank => {
+            if let Some(text) = kill_ring.latest() {
+                buffer.insert_str(text);
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Index on the line starting at `line_start` drawn in the same column as `index`, inputs
+/// drawing tabs one column wide.
+fn same_column(buffer: &GapBuffer, index: usize, line_start: usize) -> usize {
+    let column = display_width(&buffer.slice(buffer.line_start(index)..index), 1);
+    let line = buffer.slice(line_start..buffer.line_end(line_start));
+    line_start + column_index(&line, column, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, cursor: usize, actions: &[Action]) -> (String, usize, KillRing) {
+        let mut buffer = GapBuffer::from_str(text, 8);
+        buffer.move_gap(cursor);
+        let mut kill_ring = KillRing::default();
+        for action in actions {
+            assert!(handle_action(*action, &mut buffer, &mut kill_ring));
+        }
+        (buffer.to_string(), buffer.cursor(), kill_ring)
+    }
+
+    #[test]
+    fn test_movement() {
+        let text = "let foo_bar = 1;\nnext";
+        assert_eq!(run(text, 0, &[Action::CursorWordRight]).1, 3);
+        assert_eq!(run(text, 3, &[Action::CursorWordRight]).1, 11);
+        assert_eq!(run(text, 13, &[Action::CursorWordLeft]).1, 4);
+        assert_eq!(run(text, 8, &[Action::CursorLineEnd]).1, 16);
+        assert_eq!(run(text, 19, &[Action::CursorLineStart]).1, 17);
+        assert_eq!(run(text, 21, &[Action::CursorRight]).1, 21);
+        assert_eq!(run(text, 0, &[Action::CursorLeft]).1, 0);
+        assert_eq!(run(text, 8, &[Action::CursorDown]).1, 21);
+        assert_eq!(run(text, 19, &[Action::CursorUp]).1, 2);
+        assert_eq!(run(text, 2, &[Action::CursorUp]).1, 2);
+        // columns are counted on screen, wide characters taking two
+        assert_eq!(run("日本語\nabcdef", 8, &[Action::CursorUp]).1, 2);
+        assert_eq!(run("日本語\nabcdef", 1, &[Action::CursorDown]).1, 6);
+    }
+
+    #[test]
+    fn test_delete() {
+        let text = "let foo_bar = 1;\nnext";
+        assert_eq!(
+            run(text, 4, &[Action::DeleteForward]).0,
+            "let oo_bar = 1;\nnext"
+        );
+        let (text, cursor, kill_ring) = run(text, 11, &[Action::DeleteWordBackward]);
+        assert_eq!((text.as_str(), cursor), ("let  = 1;\nnext", 4));
+        assert_eq!(kill_ring.latest(), None);
+        let actions = [Action::DeleteWordForward, Action::DeleteWordForward];
+        assert_eq!(run("a.b c", 0, &actions).0, " c");
+        assert_eq!(run("one\ntwo", 5, &[Action::DeleteLine]).0, "one");
+    }
+
+    #[test]
+    fn test_kill_and_yank() {
+        let (text, cursor, kill_ring) = run("one two three", 7, &[Action::KillWordBackward]);
+        assert_eq!((text.as_str(), cursor), ("one  three", 4));
+        assert_eq!(kill_ring.latest(), Some("two"));
+
+        let (text, _, _) = run("one\ntwo", 3, &[Action::KillToLineEnd]);
+        assert_eq!(text, "onetwo");
+
+        let actions = [Action::KillToLineStart, Action::CursorLineEnd, Action::Yank];
+        let (text, cursor, _) = run("ab cd", 3, &actions);
+        assert_eq!((text.as_str(), cursor), ("cdab ", 5));
+    }
+}
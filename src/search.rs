@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+/// Index of the snippet titles, built once so that a search doesn't lowercase every title again.
+///
+/// Titles are also indexed by trigram: a query of three or more characters only has to check
+/// the titles containing its rarest trigram.
+#[derive(Default)]
+pub struct SearchIndex {
+    titles: Vec<String>,
+    // sorted indices of the titles containing each trigram.
+    trigrams: HashMap<[char; 3], Vec<usize>>,
+}
+
+impl SearchIndex {
+    pub fn new<'a>(titles: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut index = SearchIndex::default();
+        for title in titles {
+            index.push(title);
+        }
+        index
+    }
+
+    /// Indexes the title of a snippet added at the end of the list.
+    pub fn push(&mut self, title: &str) {
+        let position = self.titles.len();
+        let title = title.to_lowercase();
+        for trigram in trigrams(&title) {
+            let positions = self.trigrams.entry(trigram).or_default();
+            // a title repeating a trigram is only listed once
+            if positions.last() != Some(&position) {
+                positions.push(position);
+            }
+        }
+        self.titles.push(title);
+    }
+
+    /// Indices of the titles containing `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        let query_trigrams = trigrams(&query);
+        if query_trigrams.is_empty() {
+            return (0..self.titles.len())
+                .filter(|&i| self.titles[i].contains(&query))
+                .collect();
+        }
+        let candidates = query_trigrams
+            .iter()
+            .map(|trigram| self.trigrams.get(trigram).map_or(&[][..], Vec::as_slice))
+            .min_by_key(|positions| positions.len())
+            .unwrap_or_default();
+        candidates
+            .iter()
+            .copied()
+            .filter(|&i| self.titles[i].contains(&query))
+            .collect()
+    }
+}
+
+fn trigrams(text: &str) -> Vec<[char; 3]> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(3)
+        .map(|window| [window[0], window[1], window[2]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let mut index = SearchIndex::new(["Read File", "HTTP server", "Parse JSON"]);
+        index.push("Serve files");
+        assert_eq!(index.search(""), vec![0, 1, 2, 3]);
+        assert_eq!(index.search("se"), vec![1, 2, 3]);
+        assert_eq!(index.search("FILE"), vec![0, 3]);
+        assert_eq!(index.search("serv"), vec![1, 3]);
+        assert_eq!(index.search("json parser"), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result, bail};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::config::UiConfig;
+
+pub const PRESETS: [&str; 3] = ["dark", "light", "high-contrast"];
+
+/// Colors of the interface around the syntax highlighted code.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UiTheme {
+    /// Borders of the panes and popups.
+    pub border: Color,
+    /// Titles of the panes and popups.
+    pub title: Color,
+    /// Text of the snippet list, the search bar and the popup inputs.
+    pub text: Color,
+    /// Background of the selected item in lists.
+    pub highlight_bg: Color,
+    /// Foreground of the selected item in lists.
+    pub highlight_fg: Color,
+    /// Background of the mode badge in the status bar.
+    pub badge_bg: Color,
+    /// Foreground of the mode badge in the status bar.
+    pub badge_fg: Color,
+    /// Key hints in the status bar and the pane footers.
+    pub help: Color,
+    /// Draw titles, selected items and the badge in bold.
+    pub bold: bool,
+}
+
+impl UiTheme {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(UiTheme {
+                border: Color::Reset,
+                title: Color::Blue,
+                text: Color::Reset,
+                highlight_bg: Color::Black,
+                highlight_fg: Color::White,
+                badge_bg: Color::Blue,
+                badge_fg: Color::Black,
+                help: Color::Reset,
+                bold: false,
+            }),
+            "light" => Some(UiTheme {
+                border: Color::Gray,
+                title: Color::Blue,
+                text: Color::Black,
+                highlight_bg: Color::LightBlue,
+                highlight_fg: Color::Black,
+                badge_bg: Color::Blue,
+                badge_fg: Color::White,
+                help: Color::DarkGray,
+                bold: false,
+            }),
+            "high-contrast" => Some(UiTheme {
+                border: Color::White,
+                title: Color::Yellow,
+                text: Color::White,
+                highlight_bg: Color::Yellow,
+                highlight_fg: Color::Black,
+                badge_bg: Color::Yellow,
+                badge_fg: Color::Black,
+                help: Color::White,
+                bold: true,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Loads the preset named in the config, with the values of the theme file on top of it.
+    /// A relative theme file is found in `config_dir`.
+    pub fn load(config: &UiConfig, config_dir: &Path) -> Result<Self> {
+        let Some(preset) = UiTheme::preset(&config.theme) else {
+            bail!("ui.theme {:?} is not one of {:?}", config.theme, PRESETS);
+        };
+        let Some(path) = &config.theme_file else {
+            return Ok(preset);
+        };
+        let path = &config_dir.join(path);
+        let mut table = toml::Table::try_from(&preset)
+            .with_context(|| "could not serialize the ui theme preset")?;
+        table.extend(read_theme_file(path)?);
+        table
+            .try_into()
+            .with_context(|| format!("could not parse ui theme file {:?}", path))
+    }
+
+    pub fn border_style(&self) -> Style {
+        Style::default().fg(self.border)
+    }
+
+    pub fn title_style(&self) -> Style {
+        self.emphasize(Style::default().fg(self.title))
+    }
+
+    pub fn text_style(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    pub fn highlight_style(&self) -> Style {
+        self.emphasize(Style::default().bg(self.highlight_bg).fg(self.highlight_fg))
+    }
+
+    pub fn badge_style(&self) -> Style {
+        self.emphasize(Style::default().bg(self.badge_bg).fg(self.badge_fg))
+    }
+
+    pub fn help_style(&self) -> Style {
+        Style::default().fg(self.help)
+    }
+
+    fn emphasize(&self, style: Style) -> Style {
+        if self.bold {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    }
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        UiTheme::preset("dark").expect("the dark preset should exist")
+    }
+}
+
+fn read_theme_file(path: &Path) -> Result<toml::Table> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("could not read file {:?}", path))?;
+    content
+        .parse()
+        .with_context(|| format!("could not parse ui theme file {:?}", path))
+}
@@ -1,42 +1,67 @@
-use std::u16;
-
 use crate::app::{AppMode, AppState, Snippet};
-use crate::editor::GapBuffer;
-use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
-use ratatui::layout::{Constraint, Layout};
+use crate::clipboard;
+use crate::config::EditorConfig;
+use crate::editor::{GapBuffer, column_index, display_width, grapheme_width, wrap_line};
+use crate::find::{self, FindBar, FindInput};
+use crate::highlight::HighlightCache;
+use crate::indent::{self, IndentStyle};
+use crate::keymap::Action;
+use crate::panes::{Pane, Panes, Side, View};
+use crate::readline;
+use crate::vim::{Vim, VimMode};
+use ratatui::crossterm::event::{
+    Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Direction, Flex, Layout, Position};
 use ratatui::text::Span;
-use ratatui::widgets::{BorderType, Padding, Paragraph};
+use ratatui::widgets::{BorderType, Clear, Padding, Paragraph};
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Style, Stylize},
+    style::{Modifier, Style, Stylize},
     text::Line,
     widgets::{Block, Borders, List, ListItem, ListState},
 };
-use syntect::{
-    easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet, util::LinesWithEndings,
-};
+use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
+
+use unicode_segmentation::UnicodeSegmentation;
 
-use syntect_tui::into_span;
+use std::{collections::HashMap, ops::Range};
 
-const EDITOR_BUFFER_SIZE: usize = 1024;
 const SEARCH_BUFFER_SIZE: usize = 256;
-const TAB_SIZE: usize = 4;
-const PADDING_SIZE: u16 = 1;
+// smallest popup that still fits both inputs and the help text.
+const POPUP_MIN_WIDTH: u16 = 30;
+const POPUP_MIN_HEIGHT: u16 = 9;
+// lines taken by a snippet in the list: title, language and a blank line.
+const LIST_ITEM_HEIGHT: usize = 3;
+// appended to the title of a snippet with unsaved changes.
+const MODIFIED_MARKER: &str = " ●";
+// lines scrolled by one step of the mouse wheel.
+const SCROLL_LINES: usize = 3;
+// rows of the find bar under the text: the query and the replacement.
+const FIND_BAR_HEIGHT: u16 = 2;
+const FIND_LABEL: &str = " Find:    ";
+const REPLACE_LABEL: &str = " Replace: ";
+// characters around the visible text searched for the bracket matching the cursor's.
+const BRACKET_SCAN_MARGIN: usize = 10_000;
 
 pub trait Component {
     fn render(&mut self, area: Rect, frame: &mut Frame, state: &AppState);
-    fn handle_event(&mut self, event: &Event, state: &mut AppState);
+    fn handle_action(&mut self, action: Action, state: &mut AppState);
+    /// Receives the key events that aren't bound to an action, such as typed characters.
+    fn handle_event(&mut self, _event: &Event, _state: &mut AppState) {}
 }
 
 pub struct SnippetListComponent {
     local_state: ListState,
+    area: Rect,
 }
 
 impl SnippetListComponent {
     fn new() -> Self {
         SnippetListComponent {
             local_state: ListState::default(),
+            area: Rect::default(),
         }
     }
 }
@@ -44,51 +69,101 @@ impl SnippetListComponent {
 impl Component for SnippetListComponent {
     fn render(&mut self, area: Rect, frame: &mut Frame, state: &AppState) {
         let index = state.selected_index;
+        let length = state.filtered_indices().len();
+        let inner = Block::new().borders(Borders::all()).inner(area);
+        // snippets fully visible; a partly visible one is still drawn below them.
+        let visible = (inner.height as usize / LIST_ITEM_HEIGHT).max(1);
+        let offset = scroll_offset(self.local_state.offset(), index, visible, length);
+        *self.local_state.offset_mut() = offset;
         self.local_state.select(Some(index));
+        // only the snippets on screen are turned into items, the list scrolls a window of them.
         let items: Vec<ListItem> = state
-            .filtered_snippets()
-            .iter()
-            .map(|(_, snippet)| ListItem::from(*snippet))
+            .filtered_snippets(offset..offset + visible + 1)
+            .map(|(index, snippet)| list_item(snippet, state.is_modified(index)))
             .collect();
+        let mut window = ListState::default().with_selected(Some(index - offset));
+        let position = if length == 0 {
+            String::new()
+        } else {
+            format!(" {}/{} ", index + 1, length)
+        };
         let block = Block::new()
             .borders(Borders::all())
-            .title(" 󰅩 Snippets ".blue())
-            .title_bottom(" [a]: Add Snippet  ")
+            .title(Span::styled(" 󰅩 Snippets ", state.ui_theme.title_style()))
+            .title_bottom(Span::styled(
+                format!(
+                    " [{}]: Add Snippet  ",
+                    state.keymap.label(&AppMode::Select, Action::AddSnippet)
+                ),
+                state.ui_theme.help_style(),
+            ))
+            .title_bottom(Line::styled(position, state.ui_theme.help_style()).right_aligned())
+            .border_style(state.ui_theme.border_style())
             .title_alignment(ratatui::layout::Alignment::Center);
         let list = List::new(items)
             .block(block)
-            .highlight_style(Style::default().bg(ratatui::style::Color::Black).white());
-        frame.render_stateful_widget(list, area, &mut self.local_state);
+            .style(state.ui_theme.text_style())
+            .highlight_style(state.ui_theme.highlight_style());
+        frame.render_stateful_widget(list, area, &mut window);
+        self.area = area;
+    }
+
+    fn handle_action(&mut self, action: Action, state: &mut AppState) {
+        match action {
+            Action::SelectNext => self.select_next(state),
+            Action::SelectPrevious => self.select_previous(state),
+            Action::AddSnippet => state.mode = AppMode::Popup,
+            _ => {}
+        }
     }
 
     fn handle_event(&mut self, event: &Event, state: &mut AppState) {
-        match event {
-            Event::Key(key) => match key.code {
-                KeyCode::Char('j') => {
-                    if key.kind == KeyEventKind::Press {
-                        self.select_next(state);
-                    }
-                }
-                KeyCode::Char('k') => {
-                    if key.kind == KeyEventKind::Press {
-                        self.select_previous(state);
-                    }
+        let Event::Mouse(mouse) = event else {
+            return;
+        };
+        let length = state.filtered_indices().len();
+        match mouse.kind {
+            // the wheel doesn't wrap around like the keys do
+            MouseEventKind::ScrollDown if state.selected_index + 1 < length => {
+                state.selected_index += 1;
+            }
+            MouseEventKind::ScrollUp => {
+                state.selected_index = state.selected_index.saturating_sub(1);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let inner = Block::new().borders(Borders::all()).inner(self.area);
+                if !inner.contains(Position::new(mouse.column, mouse.row)) {
+                    return;
                 }
-                KeyCode::Char('a') => {
-                    if key.kind == KeyEventKind::Press {
-                        state.mode = AppMode::Popup
-                    }
+                let row = (mouse.row - inner.y) as usize;
+                let index = self.local_state.offset() + row / LIST_ITEM_HEIGHT;
+                if index < length {
+                    state.selected_index = index;
                 }
-                _ => {}
-            },
+            }
             _ => {}
         }
     }
 }
 
+/// First snippet shown so that `selected` stays on screen with a snippet of context around it.
+///
+/// The window only moves as far as needed, one snippet at a time, instead of jumping a page.
+fn scroll_offset(offset: usize, selected: usize, visible: usize, length: usize) -> usize {
+    let margin = if visible > 2 { 1 } else { 0 };
+    let mut offset = offset;
+    if selected < offset + margin {
+        offset = selected.saturating_sub(margin);
+    }
+    if selected + margin >= offset + visible {
+        offset = selected + margin + 1 - visible;
+    }
+    offset.min(length.saturating_sub(visible))
+}
+
 impl SnippetListComponent {
     fn select_next(&self, state: &mut AppState) {
-        let length = state.filtered_snippets().len();
+        let length = state.filtered_indices().len();
         if length == 0 {
             return;
         }
@@ -97,7 +172,7 @@ impl SnippetListComponent {
     }
 
     fn select_previous(&self, state: &mut AppState) {
-        let length = state.filtered_snippets().len();
+        let length = state.filtered_indices().len();
         if length == 0 {
             return;
         }
@@ -109,169 +184,1293 @@ impl SnippetListComponent {
     }
 }
 
-impl From<&Snippet> for ListItem<'_> {
-    fn from(value: &Snippet) -> Self {
-        let title = Line::from(value.title.clone()).bold();
-        let language = Line::from(value.language.clone()).italic();
-        ListItem::new(vec![title, language, Line::from("")])
+/// Title and language of `snippet`, the title marked when it has unsaved changes.
+fn list_item(snippet: &Snippet, modified: bool) -> ListItem<'static> {
+    let mut title = Line::from(snippet.title.clone()).bold();
+    if modified {
+        title.push_span(Span::raw(MODIFIED_MARKER));
+    }
+    let language = Line::from(snippet.language.clone()).italic();
+    ListItem::new(vec![title, language, Line::from("")])
+}
+
+/// A snippet open in the editor, with the cursor, selection and scroll of the focused pane
+/// when it shows it. Other panes keep theirs in their views.
+struct OpenBuffer {
+    gap_buffer: GapBuffer,
+    // other end of the selection, the cursor being the end that moves.
+    selection_anchor: Option<usize>,
+    // index of the first visible line.
+    scroll: usize,
+    highlight_cache: HighlightCache,
+    // revisions of the buffer last marked as modified, and last copied to the snippet.
+    marked_revision: usize,
+    synced_revision: usize,
+}
+
+impl OpenBuffer {
+    fn new(code: &str, capacity: usize) -> Self {
+        OpenBuffer {
+            gap_buffer: GapBuffer::from_str(code, capacity),
+            selection_anchor: None,
+            scroll: 0,
+            highlight_cache: HighlightCache::default(),
+            marked_revision: 0,
+            synced_revision: 0,
+        }
+    }
+
+    /// Copies the text to the snippet at `index` if it changed since the last copy.
+    fn sync(&mut self, index: usize, state: &mut AppState) {
+        if self.gap_buffer.revision() != self.synced_revision {
+            self.synced_revision = self.gap_buffer.revision();
+            state.copy_code(index, self.gap_buffer.to_string());
+        }
     }
 }
 
 pub struct EditorComponent {
-    pub gap_buffer: Option<GapBuffer>,
-    selected_index: Option<usize>,
+    // buffers of the snippets open in the panes by index in the full list; a snippet that was
+    // edited stays open so that switching snippets never loses what was typed.
+    buffers: HashMap<usize, OpenBuffer>,
+    // created from the selected snippet on the first render.
+    panes: Option<Panes>,
+    // snippet selected in the list when it was last shown in the focused pane.
+    list_selection: Option<usize>,
     pub syntax_set: SyntaxSet,
     pub theme_set: ThemeSet,
-    pub cursor_coordinates: (u16, u16),
+    config: EditorConfig,
+    vim: Option<Vim>,
+    // kept while closed so that the query and the toggles are there when it opens again.
+    find: FindBar,
+    finding: bool,
+    // long lines are wrapped rather than cut at the edge of the pane.
+    wrap: bool,
 }
 
 impl Component for EditorComponent {
     fn render(&mut self, area: Rect, frame: &mut Frame, state: &AppState) {
-        // sync local state with global state by reinitializing the gap_buffer if the selected_index changes.
-        if self.selected_index != state.get_selected_snippet_index() {
-            let content = state
-                .get_content()
-                .expect("unexpected state a snippet must be selected at all times");
-            self.gap_buffer = Some(GapBuffer::from_str(&content[..], EDITOR_BUFFER_SIZE));
-            self.selected_index = state.get_selected_snippet_index();
-        }
-        // render the gap buffer with syntax highlighting.
-        let gap_buffer = self
-            .gap_buffer
+        // the focused pane follows the snippet selected in the list
+        let selected = state.get_selected_snippet_index();
+        let followed = self.list_selection != selected;
+        if followed {
+            self.list_selection = selected;
+            let snippet =
+                selected.expect("unexpected state a snippet must be selected at all times");
+            match &mut self.panes {
+                Some(_) => {
+                    self.save_view();
+                    self.panes_mut().focused_mut().open(snippet);
+                }
+                None => self.panes = Some(Panes::new(snippet)),
+            }
+        }
+        self.open_buffers(state);
+        if followed {
+            self.load_view();
+        }
+        let panes = self.panes_mut();
+        panes.layout(area);
+        for pane in 0..panes.len() {
+            self.render_pane(pane, frame, state);
+        }
+    }
+
+    fn handle_action(&mut self, action: Action, state: &mut AppState) {
+        if action.is_layout() {
+            self.handle_layout_action(action, state);
+            return;
+        }
+        if action == Action::ToggleWrap {
+            self.wrap = !self.wrap;
+            state.set_status(if self.wrap {
+                " Soft wrap on "
+            } else {
+                " Soft wrap off "
+            });
+            self.update_cursor(state);
+            return;
+        }
+        if self.finding && self.handle_find_action(action, state) {
+            return;
+        }
+        if action == Action::Find {
+            self.open_find(state);
+            return;
+        }
+        let style = self.indent_style(state);
+        let (auto_indent, auto_close) = (self.config.auto_indent, self.config.auto_close);
+        let (wrap_width, tab_size) = (self.wrap_width(self.text_area()), self.config.tab_size);
+        let open = self.open_buffer_mut();
+        let buffer = &mut open.gap_buffer;
+        let selection = open
+            .selection_anchor
+            .map(|anchor| ordered(anchor, buffer.cursor()));
+        let extended = match action {
+            Action::ExtendSelectionLeft => Some(Action::CursorLeft),
+            Action::ExtendSelectionRight => Some(Action::CursorRight),
+            Action::ExtendSelectionUp => Some(Action::CursorUp),
+            Action::ExtendSelectionDown => Some(Action::CursorDown),
+            _ => None,
+        };
+        if let Some(movement) = extended {
+            open.selection_anchor.get_or_insert(buffer.cursor());
+            if matches!(movement, Action::CursorUp | Action::CursorDown) {
+                move_by_row(buffer, movement == Action::CursorDown, wrap_width, tab_size);
+            } else {
+                readline::handle_action(movement, buffer, &mut state.kill_ring);
+            }
+            self.update_cursor(state);
+            return;
+        }
+        match action {
+            Action::Copy => {
+                if let Some(selection) = selection {
+                    state.clipboard.copy(buffer.slice(selection));
+                }
+                self.update_cursor(state);
+                return;
+            }
+            Action::Cut => {
+                if let Some(selection) = selection {
+                    state.clipboard.copy(buffer.delete_range(selection));
+                }
+            }
+            Action::Paste => {
+                replace_selection(buffer, selection, &state.clipboard.paste());
+            }
+            Action::NewLine if auto_indent => {
+                replace_selection(buffer, selection, "");
+                indent::new_line(buffer, style);
+            }
+            Action::NewLine => {
+                replace_selection(buffer, selection, "\n");
+            }
+            Action::DeleteBackward => match selection {
+                Some(selection) => {
+                    buffer.delete_range(selection);
+                }
+                None if auto_close => indent::delete_backward(buffer),
+                None => buffer.delete_backward(),
+            },
+            Action::DeleteForward | Action::DeleteWordBackward | Action::DeleteWordForward
+                if let Some(selection) = selection =>
+            {
+                buffer.delete_range(selection);
+            }
+            Action::Indent | Action::Dedent
+                if selection
+                    .as_ref()
+                    .is_some_and(|selection| buffer.slice(selection.clone()).contains('\n')) =>
+            {
+                let selection = selection.expect("unexpected state selection must not be null");
+                let lines = if action == Action::Indent {
+                    indent::indent_lines(buffer, selection, style)
+                } else {
+                    indent::dedent_lines(buffer, selection, style)
+                };
+                buffer.commit();
+                // the lines stay selected to be indented again
+                buffer.move_gap(lines.end);
+                open.selection_anchor = Some(lines.start);
+                self.update_cursor(state);
+                return;
+            }
+            Action::Indent => {
+                replace_selection(buffer, selection, &style.unit());
+            }
+            Action::Dedent => indent::dedent_line(buffer, style),
+            Action::Undo => {
+                buffer.undo();
+            }
+            Action::Redo => {
+                buffer.redo();
+            }
+            // wrapped lines are moved through one row at a time
+            // the cursor keeps its column on screen, tabs and wide characters included
+            Action::CursorUp | Action::CursorDown => {
+                move_by_row(buffer, action == Action::CursorDown, wrap_width, tab_size);
+            }
+            action => {
+                readline::handle_action(action, buffer, &mut state.kill_ring);
+            }
+        }
+        // every action is undone on its own
+        buffer.commit();
+        open.selection_anchor = None;
+        self.update_cursor(state);
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut AppState) {
+        if self.finding && !matches!(event, Event::Mouse(_)) {
+            self.handle_find_event(event, state);
+            return;
+        }
+        let style = self.indent_style(state);
+        let auto_close = self.config.auto_close;
+        let open = self.open_buffer_mut();
+        let buffer = &mut open.gap_buffer;
+        let selection = open
+            .selection_anchor
+            .map(|anchor| ordered(anchor, buffer.cursor()));
+        match event {
+            Event::Key(key) => {
+                if let KeyCode::Char(c) = key.code {
+                    match selection {
+                        None if auto_close => indent::insert_char(buffer, c, style),
+                        selection => {
+                            replace_selection(buffer, selection, c.encode_utf8(&mut [0; 4]))
+                        }
+                    }
+                    open.selection_anchor = None;
+                }
+            }
+            Event::Paste(text) => {
+                replace_selection(buffer, selection, &clipboard::normalize_newlines(text));
+                open.selection_anchor = None;
+            }
+            Event::Mouse(mouse) => {
+                self.handle_mouse(mouse, state);
+                return;
+            }
+            _ => {}
+        }
+        self.open_buffer_mut().gap_buffer.commit();
+        self.update_cursor(state);
+    }
+}
+
+/// Replaces the tabs of `line` with spaces up to the next tab stop, since the terminal
+/// doesn't draw them.
+fn expand_tabs(line: &mut Line, tab_size: usize) {
+    let mut column = 0;
+    for span in &mut line.spans {
+        if !span.content.contains('\t') {
+            column += display_width(&span.content, tab_size);
+            continue;
+        }
+        let mut expanded = String::new();
+        for grapheme in span.content.graphemes(true) {
+            let width = grapheme_width(grapheme, column, tab_size);
+            if grapheme == "\t" {
+                expanded.push_str(&" ".repeat(width));
+            } else {
+                expanded.push_str(grapheme);
+            }
+            column += width;
+        }
+        span.content = expanded.into();
+    }
+}
+
+/// Replaces the selected text, if any, with `text`.
+fn replace_selection(buffer: &mut GapBuffer, selection: Option<Range<usize>>, text: &str) {
+    if let Some(selection) = selection {
+        buffer.delete_range(selection);
+    }
+    buffer.insert_str(text);
+}
+
+fn ordered(a: usize, b: usize) -> Range<usize> {
+    a.min(b)..a.max(b)
+}
+
+impl EditorComponent {
+    fn new(config: &EditorConfig, syntax_set: SyntaxSet, theme_set: ThemeSet) -> Self {
+        EditorComponent {
+            buffers: HashMap::new(),
+            panes: None,
+            list_selection: None,
+            syntax_set,
+            theme_set,
+            config: config.clone(),
+            vim: config.vim_mode.then(|| Vim::new(config)),
+            find: FindBar::new(),
+            finding: false,
+            wrap: config.soft_wrap,
+        }
+    }
+
+    fn panes(&self) -> &Panes {
+        self.panes
             .as_ref()
-            .expect("unexpected state buffer must not be null at this point");
-        let text: String = gap_buffer.to_string();
+            .expect("unexpected state panes must not be null at this point")
+    }
+
+    fn panes_mut(&mut self) -> &mut Panes {
+        self.panes
+            .as_mut()
+            .expect("unexpected state panes must not be null at this point")
+    }
+
+    /// Opens the buffers of the snippets shown in the panes, and closes the ones that are
+    /// neither shown nor edited.
+    fn open_buffers(&mut self, state: &AppState) {
+        let Some(panes) = &self.panes else {
+            return;
+        };
+        self.buffers
+            .retain(|&index, open| open.gap_buffer.revision() != 0 || panes.contains(index));
+        for pane in panes.iter() {
+            let snippet = pane.snippet();
+            self.buffers.entry(snippet).or_insert_with(|| {
+                let code = state.snippet(snippet).map_or("", |snippet| &snippet.code);
+                OpenBuffer::new(code, self.config.buffer_size)
+            });
+        }
+    }
+
+    fn render_pane(&mut self, pane: usize, frame: &mut Frame, state: &AppState) {
+        let panes = self.panes();
+        let focused = panes.is_focused(pane);
+        let many_panes = panes.len() > 1;
+        let pane = panes.get(pane);
+        let (area, snippet) = (pane.area, pane.snippet());
+        // other panes showing the snippet keep their own scroll
+        let pane_scroll = pane.view().filter(|_| !focused).map(|view| view.scroll);
+        let title = pane_title(pane, focused, state);
+        let inner = if focused {
+            self.text_area()
+        } else {
+            text_area(area, self.config.padding)
+        };
+        let finding = focused && self.finding;
+        let selection = if focused { self.selection() } else { None };
+        let (wrap_width, tab_size) = (self.wrap_width(inner), self.config.tab_size);
         let language = state
-            .get_current_snippet()
-            .map(|snippet| &snippet.language)
-            .unwrap();
+            .snippet(snippet)
+            .map_or("txt", |snippet| &snippet.language);
         let syntax = self
             .syntax_set
-            .find_syntax_by_extension(&language)
+            .find_syntax_by_extension(language)
             .or_else(|| self.syntax_set.find_syntax_by_extension("txt"))
             .unwrap();
-        let mut highlighter =
-            HighlightLines::new(syntax, &self.theme_set.themes["base16-eighties.dark"]);
-        let buffer_widget: Vec<Line> = LinesWithEndings::from(&text)
-            .map(|line| {
-                let spans: Vec<Span> = highlighter
-                    .highlight_line(line, &self.syntax_set)
-                    .unwrap()
-                    .into_iter()
-                    .filter_map(|segment| into_span(segment).ok())
-                    // override underline color style and background
-                    .map(|span| {
-                        let style = span
-                            .style
-                            .underline_color(ratatui::style::Color::Reset)
-                            .bg(ratatui::style::Color::Reset);
-                        Span::styled(span.content, style)
-                    })
+        let open = self
+            .buffers
+            .get_mut(&snippet)
+            .expect("unexpected state buffer must not be null at this point");
+        if let Some(index) = open.gap_buffer.take_edited_from() {
+            let line = open.gap_buffer.line_of(index);
+            open.highlight_cache.invalidate_from(line);
+        }
+        // render the visible lines of the gap buffer with syntax highlighting.
+        let gap_buffer = &open.gap_buffer;
+        let height = inner.height as usize;
+        let scroll = pane_scroll.map_or(open.scroll, |scroll| {
+            scroll.min(gap_buffer.line_count() - 1)
+        });
+        let rows = visible_rows(gap_buffer, scroll, height, wrap_width, tab_size);
+        let visible_end_line = rows
+            .last()
+            .map_or(scroll, |row| gap_buffer.line_of(row.start) + 1);
+        let visible = scroll..visible_end_line;
+        let visible_start = gap_buffer.line_start_of(visible.start);
+        let visible_end = gap_buffer.line_start_of(visible.end);
+        let mut buffer_widget = open.highlight_cache.highlight(
+            gap_buffer,
+            visible.clone(),
+            &self.syntax_set,
+            syntax,
+            (&state.theme, &self.theme_set.themes[&state.theme]),
+        );
+        let matches = if finding {
+            self.find.buffer_matches(snippet, gap_buffer).clone()
+        } else {
+            Ok(Vec::new())
+        };
+        let text = gap_buffer.slice(visible_start..visible_end);
+        let visible_range = |range: &Range<usize>| {
+            range.start.saturating_sub(visible_start)..range.end.saturating_sub(visible_start)
+        };
+        for found in matches.iter().flatten() {
+            if found.end > visible_start && found.start < visible_end {
+                let style = state.ui_theme.highlight_style();
+                style_range(&mut buffer_widget, &text, visible_range(found), style);
+            }
+        }
+        if focused {
+            // the bracket under the cursor, or else the one before it, and its match
+            let cursor = gap_buffer.cursor();
+            // brackets far out of view aren't looked for
+            let bounds = visible_start.saturating_sub(BRACKET_SCAN_MARGIN)
+                ..(visible_end + BRACKET_SCAN_MARGIN).min(gap_buffer.len());
+            let brackets = [Some(cursor), cursor.checked_sub(1)]
+                .into_iter()
+                .flatten()
+                .find_map(|index| {
+                    indent::matching_bracket(gap_buffer, index, bounds.clone())
+                        .map(|other| [index, other])
+                });
+            for index in brackets.into_iter().flatten() {
+                if (visible_start..visible_end).contains(&index) {
+                    let style =
+                        Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                    style_range(
+                        &mut buffer_widget,
+                        &text,
+                        visible_range(&(index..index + 1)),
+                        style,
+                    );
+                }
+            }
+        }
+        if let Some(selection) = &selection {
+            // the current match stands out from the other ones
+            let style = if matches
+                .as_ref()
+                .is_ok_and(|found| found.contains(selection))
+            {
+                state.ui_theme.badge_style()
+            } else {
+                state.ui_theme.highlight_style()
+            };
+            style_range(&mut buffer_widget, &text, visible_range(selection), style);
+        }
+        // lines are cut into the rows they are drawn on
+        let mut buffer_widget: Vec<Line> = buffer_widget
+            .into_iter()
+            .zip(visible)
+            .flat_map(|(line, number)| {
+                let lengths: Vec<usize> = line_rows(gap_buffer, number, wrap_width, tab_size)
+                    .iter()
+                    .map(|row| row.len())
                     .collect();
-                Line::from(spans)
+                split_line(line, &lengths)
             })
+            .take(height)
             .collect();
+        for line in &mut buffer_widget {
+            expand_tabs(line, tab_size);
+        }
+        // with several panes, the focused one stands out
+        let border_style = if focused && many_panes {
+            state.ui_theme.title_style()
+        } else {
+            state.ui_theme.border_style()
+        };
         let block = Block::default()
             .borders(Borders::ALL)
-            .title("  Editor ".blue())
-            .padding(Padding::uniform(PADDING_SIZE));
+            .title(title)
+            .border_style(border_style)
+            .padding(Padding::uniform(self.config.padding));
         let paragraph = Paragraph::new(buffer_widget).block(block);
         frame.render_widget(paragraph, area);
-        if state.focused_editor {
-            frame.set_cursor_position(self.cursor_coordinates);
+        if !focused {
+            return;
+        }
+        if finding {
+            let bar = Rect::new(inner.x, inner.bottom(), inner.width, FIND_BAR_HEIGHT)
+                .intersection(text_area(area, self.config.padding));
+            self.render_find_bar(bar, frame, state, &matches, selection);
+            return;
+        }
+        // the cursor is hidden once scrolled out of view with the mouse
+        if state.focused_editor
+            && let Some((row, column)) = self.cursor_row_column()
+        {
+            let x = (inner.x + column as u16).min(inner.right().saturating_sub(1));
+            frame.set_cursor_position((x, inner.y + row as u16));
         }
     }
 
-    fn handle_event(&mut self, event: &Event, state: &mut AppState) {
-        let buffer = self
-            .gap_buffer
-            .as_mut()
-            .expect("unexpected state buffer must not be null at this point");
+    fn handle_layout_action(&mut self, action: Action, state: &mut AppState) {
+        self.save_view();
+        let Some(panes) = self.panes.as_mut() else {
+            return;
+        };
+        match action {
+            Action::NewTab => panes.focused_mut().new_tab(),
+            Action::CloseTab => panes.focused_mut().close_tab(),
+            Action::NextTab => panes.focused_mut().next_tab(),
+            Action::PreviousTab => panes.focused_mut().previous_tab(),
+            // a horizontal split stacks the panes, a vertical one puts them side by side
+            Action::SplitHorizontal => panes.split(Direction::Vertical),
+            Action::SplitVertical => panes.split(Direction::Horizontal),
+            Action::ClosePane => panes.close(),
+            Action::FocusLeft => panes.focus_side(Side::Left),
+            Action::FocusRight => panes.focus_side(Side::Right),
+            Action::FocusUp => panes.focus_side(Side::Up),
+            Action::FocusDown => panes.focus_side(Side::Down),
+            _ => {}
+        }
+        self.show_focused(state);
+    }
+
+    /// Selects the snippet of the focused pane in the list after the focus or the tab changed.
+    fn show_focused(&mut self, state: &mut AppState) {
+        state.select_snippet(self.panes().focused().snippet());
+        self.list_selection = state.get_selected_snippet_index();
+        self.open_buffers(state);
+        self.load_view();
+        state.focus_editor();
+    }
+
+    /// Keeps the cursor, selection and scroll of the focused pane in its view, before another
+    /// pane or snippet takes the focus.
+    fn save_view(&mut self) {
+        let Some(panes) = self.panes.as_mut() else {
+            return;
+        };
+        if let Some(open) = self.buffers.get(&panes.focused().snippet()) {
+            panes.focused_mut().set_view(View {
+                cursor: open.gap_buffer.cursor(),
+                selection_anchor: open.selection_anchor,
+                scroll: open.scroll,
+            });
+        }
+    }
+
+    /// Puts back the cursor, selection and scroll of the focused pane once its buffer is open,
+    /// within the text as it may have been edited from another pane since.
+    fn load_view(&mut self) {
+        let Some(panes) = &self.panes else {
+            return;
+        };
+        let focused = panes.focused();
+        if let Some(view) = focused.view()
+            && let Some(open) = self.buffers.get_mut(&focused.snippet())
+        {
+            let len = open.gap_buffer.len();
+            open.gap_buffer.move_gap(view.cursor.min(len));
+            open.selection_anchor = view.selection_anchor.map(|anchor| anchor.min(len));
+            open.scroll = view.scroll.min(open.gap_buffer.line_count() - 1);
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: &MouseEvent, state: &mut AppState) {
+        let position = Position::new(mouse.column, mouse.row);
+        let Some(pane) = self
+            .panes
+            .as_ref()
+            .and_then(|panes| panes.pane_at(position))
+        else {
+            return;
+        };
+        // the wheel scrolls the pane under the mouse, a click also focuses it
+        let snippet = self.panes().get(pane).snippet();
+        let focused = self.panes().is_focused(pane);
+        match mouse.kind {
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let Some(open) = self.buffers.get_mut(&snippet) else {
+                    return;
+                };
+                let last_line = open.gap_buffer.line_count() - 1;
+                let scrolled = |scroll: usize| {
+                    if mouse.kind == MouseEventKind::ScrollDown {
+                        (scroll + SCROLL_LINES).min(last_line)
+                    } else {
+                        scroll.saturating_sub(SCROLL_LINES)
+                    }
+                };
+                if focused {
+                    open.scroll = scrolled(open.scroll);
+                } else {
+                    let pane = self.panes_mut().get_mut(pane);
+                    let view = pane.view().unwrap_or_default();
+                    pane.set_view(View {
+                        scroll: scrolled(view.scroll),
+                        ..view
+                    });
+                }
+                return;
+            }
+            MouseEventKind::Down(MouseButton::Left) if !focused => {
+                self.save_view();
+                self.panes_mut().focus(pane);
+                self.show_focused(state);
+            }
+            _ => {}
+        }
+        let Some(index) = self.index_at(mouse.column, mouse.row) else {
+            return;
+        };
+        let open = self.open_buffer_mut();
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                open.gap_buffer.move_gap(index);
+                open.selection_anchor = Some(index);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => open.gap_buffer.move_gap(index),
+            MouseEventKind::Up(MouseButton::Left) => {
+                // a click without a drag only places the cursor
+                if open.selection_anchor == Some(index) {
+                    open.selection_anchor = None;
+                }
+            }
+            _ => return,
+        }
+        self.update_cursor(state);
+    }
+
+    /// Scrolls the cursor into view after it moved and focuses the editor.
+    fn update_cursor(&mut self, state: &mut AppState) {
+        let height = (self.text_area().height as usize).max(1);
+        let (wrap_width, tab_size) = (self.wrap_width(self.text_area()), self.config.tab_size);
+        let open = self.open_buffer_mut();
+        let buffer = &open.gap_buffer;
+        let cursor = buffer.cursor();
+        let line = buffer.line_of(cursor);
+        if line < open.scroll {
+            open.scroll = line;
+        } else if line >= open.scroll + height {
+            open.scroll = line + 1 - height;
+        }
+        // wrapped lines above the cursor can still push its row out of view
+        while open.scroll < line
+            && row_of(
+                &visible_rows(buffer, open.scroll, height, wrap_width, tab_size),
+                cursor,
+            )
+            .is_none()
+        {
+            open.scroll += 1;
+        }
+        state.focus_editor();
+    }
+
+    /// Buffer of the focused pane, opened when it is first rendered.
+    fn open_buffer(&self) -> &OpenBuffer {
+        self.buffers
+            .get(&self.panes().focused().snippet())
+            .expect("unexpected state buffer must not be null at this point")
+    }
+
+    fn open_buffer_mut(&mut self) -> &mut OpenBuffer {
+        let snippet = self.panes().focused().snippet();
+        self.buffers
+            .get_mut(&snippet)
+            .expect("unexpected state buffer must not be null at this point")
+    }
+
+    /// Row of the cursor in the text area of the focused pane, and its column, unless it is
+    /// scrolled out of view.
+    fn cursor_row_column(&self) -> Option<(usize, usize)> {
+        let open = self.open_buffer();
+        let buffer = &open.gap_buffer;
+        let inner = self.text_area();
+        let tab_size = self.config.tab_size;
+        let rows = visible_rows(
+            buffer,
+            open.scroll,
+            inner.height as usize,
+            self.wrap_width(inner),
+            tab_size,
+        );
+        let cursor = buffer.cursor();
+        let row = row_of(&rows, cursor)?;
+        let column = display_width(&buffer.slice(rows[row].start..cursor), tab_size);
+        Some((row, column))
+    }
+
+    /// Width that the lines of a text area are wrapped to, if they are.
+    fn wrap_width(&self, area: Rect) -> Option<usize> {
+        self.wrap.then_some((area.width as usize).max(1))
+    }
+
+    /// Indentation of the snippet in the focused pane.
+    fn indent_style(&self, state: &AppState) -> IndentStyle {
+        let language = state
+            .snippet(self.panes().focused().snippet())
+            .map_or("", |snippet| &snippet.language);
+        self.config.indent_style(language)
+    }
+
+    /// Text area of the focused pane, above the find bar when it is open.
+    fn text_area(&self) -> Rect {
+        let mut area = text_area(self.panes().focused().area, self.config.padding);
+        if self.finding {
+            area.height = area.height.saturating_sub(FIND_BAR_HEIGHT);
+        }
+        area
+    }
+
+    /// Selected range of the focused buffer, from a visual selection in vim mode.
+    fn selection(&self) -> Option<Range<usize>> {
+        let open = self.open_buffer();
+        let buffer = &open.gap_buffer;
+        if let Some(vim) = &self.vim
+            && vim.mode() == VimMode::Visual
+        {
+            return vim.selection(buffer);
+        }
+        open.selection_anchor
+            .map(|anchor| ordered(anchor, buffer.cursor()))
+            .filter(|selection| !selection.is_empty())
+    }
+
+    /// Index in the focused buffer of the character shown at the screen position `column`, `row`.
+    fn index_at(&self, column: u16, row: u16) -> Option<usize> {
+        let open = self.open_buffer();
+        let buffer = &open.gap_buffer;
+        let inner = self.text_area();
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        let rows = visible_rows(
+            buffer,
+            open.scroll,
+            inner.height as usize,
+            self.wrap_width(inner),
+            self.config.tab_size,
+        );
+        let row = (row - inner.y) as usize;
+        if row >= rows.len() {
+            return Some(buffer.len());
+        }
+        let column = (column - inner.x) as usize;
+        Some(index_in_row(
+            buffer,
+            &rows,
+            row,
+            column,
+            self.config.tab_size,
+        ))
+    }
+
+    /// Shows the query, the replacement and the toggles of the find bar in `area`.
+    fn render_find_bar(
+        &self,
+        area: Rect,
+        frame: &mut Frame,
+        state: &AppState,
+        matches: &Result<Vec<Range<usize>>, regex::Error>,
+        selection: Option<Range<usize>>,
+    ) {
+        let ui_theme = &state.ui_theme;
+        let count = match matches {
+            _ if self.find.query.is_empty() => String::new(),
+            Ok(matches) if matches.is_empty() => String::from(" no matches "),
+            Ok(matches) => match matches
+                .iter()
+                .position(|found| Some(found) == selection.as_ref())
+            {
+                Some(current) => format!(" {}/{} ", current + 1, matches.len()),
+                None => format!(" {} matches ", matches.len()),
+            },
+            Err(_) => String::from(" invalid regex "),
+        };
+        let toggle = |on: bool, label: &'static str| {
+            let style = if on {
+                ui_theme.badge_style()
+            } else {
+                ui_theme.help_style()
+            };
+            Span::styled(label, style)
+        };
+        let [query_row, replacement_row] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(area);
+        frame.render_widget(Clear, area);
+        let input = |label: &'static str, buffer: &GapBuffer| {
+            Line::from(vec![
+                Span::styled(label, ui_theme.title_style()),
+                Span::styled(buffer.to_string(), ui_theme.text_style()),
+            ])
+        };
+        frame.render_widget(input(FIND_LABEL, &self.find.query), query_row);
+        frame.render_widget(
+            input(REPLACE_LABEL, &self.find.replacement),
+            replacement_row,
+        );
+        let toggles = Line::from(vec![
+            Span::styled(count, ui_theme.help_style()),
+            toggle(self.find.regex, " .* "),
+            Span::raw(" "),
+            toggle(self.find.case_sensitive, " Aa "),
+        ]);
+        frame.render_widget(toggles.right_aligned(), query_row);
+        if !state.focused_editor {
+            return;
+        }
+        let (row, label, buffer) = match self.find.focused_input {
+            FindInput::Query => (query_row, FIND_LABEL, &self.find.query),
+            FindInput::Replacement => (replacement_row, REPLACE_LABEL, &self.find.replacement),
+        };
+        let width = display_width(label, 1) + display_width(&buffer.slice(0..buffer.cursor()), 1);
+        let x = (row.x + width as u16).min(row.right().saturating_sub(1));
+        frame.set_cursor_position((x, row.y));
+    }
+
+    /// Applies `action` to the find bar, returns false for the ones left to the buffer.
+    fn handle_find_action(&mut self, action: Action, state: &mut AppState) -> bool {
+        let query = self.find.query.to_string();
+        let find = &mut self.find;
+        match action {
+            Action::Undo | Action::Redo => return false,
+            Action::Find => find.focused_input = FindInput::Query,
+            Action::Indent => find.toggle_input(),
+            Action::ToggleRegex => {
+                find.regex = !find.regex;
+                self.select_match_from_selection(state);
+            }
+            Action::ToggleCase => {
+                find.case_sensitive = !find.case_sensitive;
+                self.select_match_from_selection(state);
+            }
+            Action::CursorDown => self.select_match(true, state),
+            Action::CursorUp => self.select_match(false, state),
+            Action::NewLine => match find.focused_input {
+                FindInput::Query => self.select_match(true, state),
+                FindInput::Replacement => self.replace_match(state),
+            },
+            Action::ReplaceAll => self.replace_all(state),
+            Action::DeleteBackward => find.input_mut().delete_backward(),
+            Action::Paste => find
+                .input_mut()
+                .insert_str(&single_line(&state.clipboard.paste())),
+            action => {
+                readline::handle_action(action, find.input_mut(), &mut state.kill_ring);
+            }
+        }
+        self.find_query_edited(&query, state);
+        true
+    }
+
+    fn handle_find_event(&mut self, event: &Event, state: &mut AppState) {
+        let query = self.find.query.to_string();
         match event {
             Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char(c) => {
-                            buffer.insert_char(c);
-                        }
-                        KeyCode::Enter => {
-                            buffer.insert_char('\n');
-                        }
-                        KeyCode::Backspace => {
-                            buffer.delete_char();
-                        }
-                        KeyCode::Left => {
-                            buffer.move_gap(buffer.gap_start.saturating_sub(1));
-                        }
-                        KeyCode::Right => {
-                            buffer.move_gap(buffer.gap_start + 1);
-                        }
-                        KeyCode::Tab => {
-                            for _ in 0..TAB_SIZE {
-                                buffer.insert_char(' ');
-                            }
-                        }
-                        _ => {}
-                    }
-                    let text_before_cursor = &buffer.buffer[..buffer.gap_start];
-                    let line_count = text_before_cursor.iter().filter(|&&c| c == '\n').count() + 1;
-                    let last_newline = text_before_cursor
-                        .iter()
-                        .rposition(|&c| c == '\n')
-                        .map(|p| p + 1)
-                        .unwrap_or(0);
-                    let column = buffer.gap_start - last_newline;
-                    self.cursor_coordinates = (
-                        state.current_area.x + PADDING_SIZE + column as u16 + 1,
-                        state.current_area.y + PADDING_SIZE + line_count as u16,
-                    );
-                    state.focus_editor();
+                if let KeyCode::Char(c) = key.code {
+                    self.find.input_mut().insert_char(c);
                 }
             }
+            Event::Paste(text) => self.find.input_mut().insert_str(&single_line(text)),
             _ => {}
         }
+        self.find_query_edited(&query, state);
     }
-}
 
-impl EditorComponent {
-    fn new() -> Self {
-        let syntax_set = SyntaxSet::load_defaults_nonewlines();
-        let theme_set = ThemeSet::load_defaults();
-        EditorComponent {
-            gap_buffer: None,
-            selected_index: None,
-            cursor_coordinates: (0, 0),
-            syntax_set,
-            theme_set,
+    /// Opens the find bar, searching for the selected text if it fits on a line.
+    fn open_find(&mut self, state: &mut AppState) {
+        let selected = self
+            .selection()
+            .map(|selection| self.open_buffer().gap_buffer.slice(selection))
+            .filter(|text| !text.contains('\n'));
+        if let Some(text) = selected {
+            self.find.set_query(&text);
+        }
+        self.find.focused_input = FindInput::Query;
+        self.finding = true;
+        self.select_match_from_selection(state);
+    }
+
+    /// Closes the find bar, returns whether it was open.
+    pub fn close_find(&mut self) -> bool {
+        if !self.finding {
+            return false;
+        }
+        self.finding = false;
+        // the vim emulation has its own selection
+        if self.vim.is_some() && self.panes.is_some() {
+            self.open_buffer_mut().selection_anchor = None;
+        }
+        true
+    }
+
+    pub fn is_finding(&self) -> bool {
+        self.finding
+    }
+
+    /// Matches of the query in the focused buffer, none if it isn't a valid pattern.
+    fn find_matches(&mut self) -> Vec<Range<usize>> {
+        let snippet = self.panes().focused().snippet();
+        let buffer = &self.buffers[&snippet].gap_buffer;
+        self.find
+            .buffer_matches(snippet, buffer)
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Looks for the query again from the current match after it was edited.
+    fn find_query_edited(&mut self, previous: &str, state: &mut AppState) {
+        if self.find.query.to_string() != previous {
+            self.select_match_from_selection(state);
+        }
+    }
+
+    fn select_match_from_selection(&mut self, state: &mut AppState) {
+        let open = self.open_buffer();
+        let cursor = open.gap_buffer.cursor();
+        let start = open
+            .selection_anchor
+            .map_or(cursor, |anchor| anchor.min(cursor));
+        let found = find::next_match(&self.find_matches(), start);
+        self.select_found(found, state);
+    }
+
+    /// Selects the match after the cursor, or the one before the selection.
+    fn select_match(&mut self, forward: bool, state: &mut AppState) {
+        let matches = self.find_matches();
+        let open = self.open_buffer();
+        let cursor = open.gap_buffer.cursor();
+        let found = if forward {
+            find::next_match(&matches, cursor)
+        } else {
+            let start = open
+                .selection_anchor
+                .map_or(cursor, |anchor| anchor.min(cursor));
+            find::previous_match(&matches, start)
+        };
+        self.select_found(found, state);
+    }
+
+    fn select_found(&mut self, found: Option<Range<usize>>, state: &mut AppState) {
+        let open = self.open_buffer_mut();
+        match found {
+            Some(found) => {
+                open.gap_buffer.move_gap(found.end);
+                open.selection_anchor = Some(found.start);
+            }
+            None => open.selection_anchor = None,
+        }
+        self.update_cursor(state);
+    }
+
+    /// Replaces the current match and selects the next one.
+    fn replace_match(&mut self, state: &mut AppState) {
+        let matches = self.find_matches();
+        let snippet = self.panes().focused().snippet();
+        let open = self
+            .buffers
+            .get_mut(&snippet)
+            .expect("unexpected state buffer must not be null at this point");
+        let buffer = &mut open.gap_buffer;
+        let current = open
+            .selection_anchor
+            .map(|anchor| ordered(anchor, buffer.cursor()))
+            .filter(|selection| matches.contains(selection));
+        if let Some(current) = current {
+            let replacement = self
+                .find
+                .replacement_for(&buffer.to_string(), current.clone());
+            replace_selection(buffer, Some(current), &replacement);
+            buffer.commit();
+            open.selection_anchor = None;
+        }
+        self.select_match(true, state);
+    }
+
+    /// Replaces every match as a single undoable change.
+    fn replace_all(&mut self, state: &mut AppState) {
+        let matches = self.find_matches();
+        let snippet = self.panes().focused().snippet();
+        let open = self
+            .buffers
+            .get_mut(&snippet)
+            .expect("unexpected state buffer must not be null at this point");
+        let buffer = &mut open.gap_buffer;
+        let text = buffer.to_string();
+        // from the last match so that the ranges of the others stay valid
+        for found in matches.iter().rev() {
+            let replacement = self.find.replacement_for(&text, found.clone());
+            replace_selection(buffer, Some(found.clone()), &replacement);
         }
+        buffer.commit();
+        open.selection_anchor = None;
+        state.set_status(format!(" Replaced {} matches ", matches.len()));
+        self.update_cursor(state);
+    }
+
+    /// Sub-mode of the vim emulation, if it is enabled.
+    pub fn vim_mode(&self) -> Option<VimMode> {
+        self.vim.as_ref().map(|vim| vim.mode())
     }
 
+    /// Whether a key has to go to the vim emulation even if it is bound in the keymap,
+    /// which is the case until vim is back to an idle normal mode.
+    pub fn captures_keys(&self) -> bool {
+        self.vim.as_ref().is_some_and(|vim| !vim.is_idle())
+    }
+
+    pub fn handle_vim_key(&mut self, key: &KeyEvent, state: &mut AppState) {
+        if self.panes.is_none() {
+            return;
+        }
+        let snippet = self.panes().focused().snippet();
+        let style = self.indent_style(state);
+        if let Some(vim) = self.vim.as_mut()
+            && let Some(open) = self.buffers.get_mut(&snippet)
+        {
+            vim.handle_key(key, &mut open.gap_buffer, style);
+        }
+        self.update_cursor(state);
+    }
+
+    /// Snippet of the focused pane.
+    pub fn focused_snippet(&self) -> Option<usize> {
+        self.panes.as_ref().map(|panes| panes.focused().snippet())
+    }
+
+    /// Replaces the text of the buffer of `snippet`, if it is open, after it changed elsewhere.
+    pub fn reload(&mut self, snippet: usize, code: &str) {
+        if let Some(open) = self.buffers.get_mut(&snippet) {
+            let cursor = open.gap_buffer.cursor();
+            let mut reloaded = OpenBuffer::new(code, self.config.buffer_size);
+            reloaded
+                .gap_buffer
+                .move_gap(cursor.min(reloaded.gap_buffer.len()));
+            reloaded.scroll = open.scroll;
+            *open = reloaded;
+            self.find.forget_matches();
+        }
+    }
+
+    /// Marks the snippets of the buffers edited since the last call as modified. Only the text
+    /// of the buffers the focus left is copied, the focused one is edited key after key.
+    pub fn track_edits(&mut self, state: &mut AppState) {
+        let focused = self.focused_snippet();
+        for (&index, open) in &mut self.buffers {
+            if open.gap_buffer.revision() != open.marked_revision {
+                open.marked_revision = open.gap_buffer.revision();
+                state.mark_modified(index);
+            }
+            if Some(index) != focused {
+                open.sync(index, state);
+            }
+        }
+    }
+
+    /// Copies the open buffers edited since the last copy to their snippets, before they are
+    /// written or read.
     pub fn sync_buffer_to_state(&mut self, state: &mut AppState) {
-        let gap_buffer = self
-            .gap_buffer
-            .as_ref()
-            .expect("buffer should be defined at this point");
-        let new_code: String = gap_buffer.to_string();
+        for (&index, open) in &mut self.buffers {
+            open.sync(index, state);
+        }
+    }
+}
+
+/// Ranges of the rows `line` is drawn on: the whole line, or its parts once wrapped to
+/// `wrap_width` columns.
+fn line_rows(
+    buffer: &GapBuffer,
+    line: usize,
+    wrap_width: Option<usize>,
+    tab_size: usize,
+) -> Vec<Range<usize>> {
+    let start = buffer.line_start_of(line);
+    let end = buffer.line_end(start);
+    let Some(width) = wrap_width else {
+        return std::iter::once(start..end).collect();
+    };
+    let starts: Vec<usize> = wrap_line(&buffer.slice(start..end), width, tab_size)
+        .into_iter()
+        .map(|offset| start + offset)
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(row, &row_start)| row_start..starts.get(row + 1).copied().unwrap_or(end))
+        .collect()
+}
+
+/// Rows that fit in `height` from the line `scroll` on.
+fn visible_rows(
+    buffer: &GapBuffer,
+    scroll: usize,
+    height: usize,
+    wrap_width: Option<usize>,
+    tab_size: usize,
+) -> Vec<Range<usize>> {
+    let mut rows = Vec::new();
+    let mut line = scroll;
+    while rows.len() < height && line < buffer.line_count() {
+        rows.extend(line_rows(buffer, line, wrap_width, tab_size));
+        line += 1;
+    }
+    rows.truncate(height);
+    rows
+}
 
-        // Update the actual snippet in the original list
-        if let Some(actual_index) = state.get_selected_snippet_index() {
-            if let Some(snippet) = state.snippet_list.get_mut(actual_index) {
-                snippet.code = new_code;
+/// Row of `rows` that the cursor at `index` is drawn on, the end of a wrapped row being the
+/// start of the next one.
+fn row_of(rows: &[Range<usize>], index: usize) -> Option<usize> {
+    rows.iter()
+        .rposition(|row| row.start <= index && index <= row.end)
+}
+
+/// Index of the character drawn at `column` of `rows[row]`.
+fn index_in_row(
+    buffer: &GapBuffer,
+    rows: &[Range<usize>],
+    row: usize,
+    column: usize,
+    tab_size: usize,
+) -> usize {
+    let range = rows[row].clone();
+    let mut index = range.start + column_index(&buffer.slice(range.clone()), column, tab_size);
+    // the end of a wrapped row is drawn at the start of the next one
+    if index == range.end
+        && index > range.start
+        && rows
+            .get(row + 1)
+            .is_some_and(|next| next.start == range.end)
+    {
+        index = buffer.previous_boundary(index);
+    }
+    index
+}
+
+/// Moves the cursor to the row above or below it, lines being wrapped to `wrap_width`, keeping
+/// its column.
+fn move_by_row(buffer: &mut GapBuffer, down: bool, wrap_width: Option<usize>, tab_size: usize) {
+    let cursor = buffer.cursor();
+    let line = buffer.line_of(cursor);
+    let rows = line_rows(buffer, line, wrap_width, tab_size);
+    let row = row_of(&rows, cursor).unwrap_or(0);
+    let column = display_width(&buffer.slice(rows[row].start..cursor), tab_size);
+    let (rows, row) = if down && row + 1 < rows.len() {
+        (rows, row + 1)
+    } else if down && line + 1 < buffer.line_count() {
+        (line_rows(buffer, line + 1, wrap_width, tab_size), 0)
+    } else if !down && row > 0 {
+        (rows, row - 1)
+    } else if !down && line > 0 {
+        let rows = line_rows(buffer, line - 1, wrap_width, tab_size);
+        let last = rows.len() - 1;
+        (rows, last)
+    } else {
+        return;
+    };
+    buffer.move_gap(index_in_row(buffer, &rows, row, column, tab_size));
+}
+
+/// Cuts `line` into rows of `lengths` characters, the last row taking the rest.
+fn split_line(line: Line<'static>, lengths: &[usize]) -> Vec<Line<'static>> {
+    if lengths.len() <= 1 {
+        return vec![line];
+    }
+    let mut rows: Vec<Vec<Span>> = vec![Vec::new()];
+    let mut remaining = lengths[0];
+    for span in line.spans {
+        let mut content: &str = &span.content;
+        while !content.is_empty() {
+            if remaining == 0 && rows.len() < lengths.len() {
+                remaining = lengths[rows.len()];
+                rows.push(Vec::new());
+                continue;
             }
+            let last = rows.len() == lengths.len();
+            let count = content.chars().count();
+            let taken = if last { count } else { remaining.min(count) };
+            let end = content
+                .char_indices()
+                .nth(taken)
+                .map_or(content.len(), |(byte, _)| byte);
+            let row = rows
+                .last_mut()
+                .expect("unexpected state rows must not be empty");
+            row.push(Span::styled(content[..end].to_string(), span.style));
+            remaining -= taken.min(remaining);
+            content = &content[end..];
         }
     }
+    rows.resize(lengths.len(), Vec::new());
+    rows.into_iter()
+        .map(|spans| Line::from(spans).style(line.style))
+        .collect()
+}
+
+/// Area of the text inside the borders and the padding of a pane.
+fn text_area(area: Rect, padding: u16) -> Rect {
+    Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::uniform(padding))
+        .inner(area)
+}
+
+/// Title of a pane: its snippet, or the bar of its tabs when it has several.
+fn pane_title(pane: &Pane, focused: bool, state: &AppState) -> Line<'static> {
+    let ui_theme = &state.ui_theme;
+    let marker = |snippet| {
+        if state.is_modified(snippet) {
+            MODIFIED_MARKER
+        } else {
+            ""
+        }
+    };
+    if pane.tabs.len() == 1 {
+        let title = format!("  Editor{} ", marker(pane.snippet()));
+        return Line::from(Span::styled(title, ui_theme.title_style()));
+    }
+    let mut spans = vec![Span::styled(" ", ui_theme.title_style())];
+    for (position, &snippet) in pane.tabs.iter().enumerate() {
+        let title = state.snippet(snippet).map_or("", |snippet| &snippet.title);
+        let style = if position != pane.active {
+            ui_theme.help_style()
+        } else if focused {
+            ui_theme.badge_style()
+        } else {
+            ui_theme.title_style()
+        };
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" {}{} ", title, marker(snippet)),
+            style,
+        ));
+    }
+    spans.push(Span::raw(" "));
+    Line::from(spans)
+}
+
+/// Patches `style` over the characters of `text` in `range`, `lines` being the rendered lines
+/// of `text`.
+fn style_range(lines: &mut [Line], text: &str, range: Range<usize>, style: Style) {
+    let mut line_start = 0;
+    for (line, content) in lines.iter_mut().zip(text.split_inclusive('\n')) {
+        let line_end = line_start + content.chars().count();
+        let start = range.start.max(line_start);
+        let end = range.end.min(line_end);
+        if start < end {
+            let mut spans = Vec::new();
+            let mut span_start = line_start;
+            for span in line.spans.drain(..) {
+                let chars: Vec<char> = span.content.chars().collect();
+                let span_end = span_start + chars.len();
+                let from = start.clamp(span_start, span_end) - span_start;
+                let to = end.clamp(span_start, span_end) - span_start;
+                let parts = [
+                    (&chars[..from], span.style),
+                    (&chars[from..to], span.style.patch(style)),
+                    (&chars[to..], span.style),
+                ];
+                for (part, part_style) in parts {
+                    if !part.is_empty() {
+                        spans.push(Span::styled(part.iter().collect::<String>(), part_style));
+                    }
+                }
+                span_start = span_end;
+            }
+            line.spans = spans;
+        }
+        line_start = line_end;
+    }
+}
+
+/// Rectangle of at most `width` by `height` in the middle of `area`.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Joins the lines of text pasted into a single line input.
+fn single_line(text: &str) -> String {
+    clipboard::normalize_newlines(text).replace(['\n', '\t'], " ")
 }
 
 pub struct SearchComponent {
     gap_buffer: GapBuffer,
-    coordinates: (u16, u16),
+    area: Rect,
 }
 
 impl SearchComponent {
     fn new() -> Self {
         SearchComponent {
             gap_buffer: GapBuffer::from_str("", SEARCH_BUFFER_SIZE),
-            coordinates: (0, 0),
+            area: Rect::default(),
         }
     }
 }
@@ -279,46 +1478,77 @@ impl SearchComponent {
 impl Component for SearchComponent {
     fn render(&mut self, area: Rect, frame: &mut Frame, state: &AppState) {
         let block = Block::default()
-            .title_top("  Search ".blue())
-            .borders(Borders::ALL);
+            .title_top(Span::styled("  Search ", state.ui_theme.title_style()))
+            .borders(Borders::ALL)
+            .border_style(state.ui_theme.border_style());
         let text: String = self.gap_buffer.to_string();
-        let line = Paragraph::new(text).block(block);
+        let line = Paragraph::new(text)
+            .style(state.ui_theme.text_style())
+            .block(block);
         frame.render_widget(line, area);
+        self.area = area;
         if state.focused_search {
-            frame.set_cursor_position(self.coordinates);
+            frame.set_cursor_position(input_cursor(area, &self.gap_buffer));
         }
     }
 
+    fn handle_action(&mut self, action: Action, state: &mut AppState) {
+        match action {
+            Action::DeleteBackward => {
+                self.gap_buffer.delete_backward();
+            }
+            Action::ApplySearch => {
+                state.set_search_query(self.gap_buffer.to_string());
+                state.selected_index = 0;
+            }
+            action => {
+                readline::handle_action(action, &mut self.gap_buffer, &mut state.kill_ring);
+            }
+        }
+        self.update_cursor(state);
+    }
+
     fn handle_event(&mut self, event: &Event, state: &mut AppState) {
         match event {
             Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char(c) => {
-                            self.gap_buffer.insert_char(c);
-                        }
-                        KeyCode::Backspace => {
-                            self.gap_buffer.delete_char();
-                        }
-                        KeyCode::Enter => {
-                            state.search_query = self.gap_buffer.to_string();
-                            state.selected_index = 0;
-                        }
-                        _ => {}
-                    }
-                    // set cursor position
-                    let x: u16 = state.current_area.x + self.gap_buffer.gap_start as u16 + 1;
-                    let y: u16 = state.current_area.y + 1;
-                    self.coordinates = (x, y);
-                    // focused_search
-                    state.focus_search();
+                if let KeyCode::Char(c) = key.code {
+                    self.gap_buffer.insert_char(c);
+                }
+            }
+            Event::Paste(text) => self.gap_buffer.insert_str(&single_line(text)),
+            Event::Mouse(mouse) => {
+                if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+                    return;
                 }
+                let column = mouse.column.saturating_sub(self.area.x + 1) as usize;
+                self.gap_buffer
+                    .move_gap(input_index(&self.gap_buffer, column));
             }
             _ => {}
         }
+        self.update_cursor(state);
+    }
+}
+
+impl SearchComponent {
+    fn update_cursor(&mut self, state: &mut AppState) {
+        // the position itself is computed when rendering
+        state.focus_search();
     }
 }
 
+/// Screen position of the cursor of a single line input drawn with borders in `area`.
+fn input_cursor(area: Rect, buffer: &GapBuffer) -> Position {
+    let width = display_width(&buffer.slice(0..buffer.cursor()), 1);
+    let x = area.x + 1 + width as u16;
+    Position::new(x.min(area.right().saturating_sub(2)), area.y + 1)
+}
+
+/// Index of the character drawn at `column` of a single line input.
+fn input_index(buffer: &GapBuffer, column: usize) -> usize {
+    column_index(&buffer.to_string(), column, 1)
+}
+
 #[derive(PartialEq)]
 enum Input {
     Title,
@@ -328,24 +1558,19 @@ enum Input {
 pub struct AddSnippetPopupComponent {
     title_input: GapBuffer,
     language_input: GapBuffer,
-    cursor_position: (u16, u16),
     focused_input: Input,
     current_area: Rect,
-    should_show_cursor: bool,
     title_area: Rect,
     language_area: Rect,
 }
 
 impl Component for AddSnippetPopupComponent {
-    fn render(&mut self, _area: Rect, frame: &mut Frame, _state: &AppState) {
-        let width = frame.area().width / 3;
-        let height = frame.area().height / 3;
-        let area = Rect::new(
-            frame.area().width / 2 - width / 2,
-            frame.area().height / 2 - height / 2,
-            width,
-            height,
-        );
+    fn render(&mut self, _area: Rect, frame: &mut Frame, state: &AppState) {
+        let ui_theme = &state.ui_theme;
+        // a third of the screen, but large enough for both inputs
+        let width = (frame.area().width / 3).max(POPUP_MIN_WIDTH);
+        let height = (frame.area().height / 3).max(POPUP_MIN_HEIGHT);
+        let area = centered(frame.area(), width, height);
         let layout = Layout::new(
             ratatui::layout::Direction::Vertical,
             vec![
@@ -361,22 +1586,35 @@ impl Component for AddSnippetPopupComponent {
 
         // block definitions
         let title_block = Block::default()
-            .title(" Snippet Title ")
+            .title(Span::styled(" Snippet Title ", ui_theme.title_style()))
             .title_alignment(ratatui::layout::Alignment::Left)
             .borders(Borders::ALL)
-            .border_type(BorderType::Rounded);
+            .border_type(BorderType::Rounded)
+            .border_style(ui_theme.border_style());
 
         let language_block = Block::default()
-            .title(" Language Extension ")
+            .title(Span::styled(" Language Extension ", ui_theme.title_style()))
             .title_alignment(ratatui::layout::Alignment::Left)
             .borders(Borders::ALL)
-            .border_type(BorderType::Rounded);
+            .border_type(BorderType::Rounded)
+            .border_style(ui_theme.border_style());
 
-        let title = Paragraph::new(self.title_input.to_string()).block(title_block);
-        let language = Paragraph::new(self.language_input.to_string()).block(language_block);
+        let title = Paragraph::new(self.title_input.to_string())
+            .style(ui_theme.text_style())
+            .block(title_block);
+        let language = Paragraph::new(self.language_input.to_string())
+            .style(ui_theme.text_style())
+            .block(language_block);
 
         let help_text = Block::default()
-            .title("[A]: Add | [Esc]: Close")
+            .title(Span::styled(
+                format!(
+                    "[{}]: Add | [{}]: Close",
+                    state.keymap.label(&AppMode::Popup, Action::Submit),
+                    state.keymap.label(&AppMode::Popup, Action::Back)
+                ),
+                ui_theme.help_style(),
+            ))
             .title_alignment(ratatui::layout::Alignment::Center);
 
         frame.render_widget(language, layout[0]);
@@ -396,39 +1634,56 @@ impl Component for AddSnippetPopupComponent {
             }
         }
 
-        if self.should_show_cursor {
-            assert!(self.cursor_position != (0, 0));
-            frame.set_cursor_position(self.cursor_position);
+        let buffer = match self.focused_input {
+            Input::Title => &self.title_input,
+            Input::Language => &self.language_input,
+        };
+        frame.set_cursor_position(input_cursor(self.current_area, buffer));
+    }
+
+    fn handle_action(&mut self, action: Action, state: &mut AppState) {
+        match action {
+            Action::Submit => {
+                let snippet = Snippet {
+                    title: self.title_input.to_string(),
+                    language: self.language_input.to_string(),
+                    code: String::new(),
+                };
+                state.add_snippet(snippet);
+                state.mode = AppMode::Command;
+            }
+            Action::DeleteBackward => self.active_buffer().delete_backward(),
+            Action::NextInput => self.toggle_focused_input(),
+            action => {
+                readline::handle_action(action, self.active_buffer(), &mut state.kill_ring);
+            }
         }
     }
 
-    fn handle_event(&mut self, event: &Event, state: &mut AppState) {
+    fn handle_event(&mut self, event: &Event, _state: &mut AppState) {
         match event {
             Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('A') => {
-                            let snippet = Snippet {
-                                title: self.title_input.to_string(),
-                                language: self.language_input.to_string(),
-                                code: String::new(),
-                            };
-                            state.snippet_list.push(snippet);
-                            state.mode = AppMode::Command;
-                        }
-                        KeyCode::Char(c) => {
-                            self.insert_char(c);
-                            self.should_show_cursor = true;
-                        }
-                        KeyCode::Backspace => {
-                            self.delete_char();
-                            self.should_show_cursor = true;
-                        }
-                        KeyCode::Enter => self.toggle_focused_input(),
-                        _ => {}
-                    }
+                if let KeyCode::Char(c) = key.code {
+                    self.active_buffer().insert_char(c);
                 }
             }
+            Event::Paste(text) => self.active_buffer().insert_str(&single_line(text)),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                let position = Position::new(mouse.column, mouse.row);
+                let clicked = if self.title_area.contains(position) {
+                    Input::Title
+                } else if self.language_area.contains(position) {
+                    Input::Language
+                } else {
+                    return;
+                };
+                if clicked != self.focused_input {
+                    self.toggle_focused_input();
+                }
+                let column = mouse.column.saturating_sub(self.current_area.x + 1) as usize;
+                let buffer = self.active_buffer();
+                buffer.move_gap(input_index(buffer, column));
+            }
             _ => {}
         }
     }
@@ -439,10 +1694,8 @@ impl AddSnippetPopupComponent {
         AddSnippetPopupComponent {
             title_input: GapBuffer::from_str("", SEARCH_BUFFER_SIZE),
             language_input: GapBuffer::from_str("", SEARCH_BUFFER_SIZE),
-            cursor_position: (0, 0),
             focused_input: Input::Language,
             current_area: Rect::default(),
-            should_show_cursor: false,
             title_area: Rect::default(),
             language_area: Rect::default(),
         }
@@ -455,35 +1708,91 @@ impl AddSnippetPopupComponent {
         }
     }
 
-    fn insert_char(&mut self, c: char) {
-        let buffer = self.active_buffer();
-        buffer.insert_char(c);
-        let gap_start = buffer.gap_start;
-        self.update_cursor_position(gap_start as u16);
-    }
-
-    fn delete_char(&mut self) {
-        let buffer = self.active_buffer();
-        buffer.delete_char();
-        let gap_start = buffer.gap_start;
-        self.update_cursor_position(gap_start as u16);
-    }
-
-    fn update_cursor_position(&mut self, gap_start: u16) {
-        let x = self.current_area.x + gap_start as u16 + 1;
-        let y = self.current_area.y + 1;
-        self.cursor_position = (x, y);
-    }
-
     fn toggle_focused_input(&mut self) {
         if self.focused_input == Input::Title {
             self.focused_input = Input::Language;
             self.current_area = self.language_area;
-            self.update_cursor_position(self.language_input.gap_start as u16);
         } else {
             self.focused_input = Input::Title;
             self.current_area = self.title_area;
-            self.update_cursor_position(self.title_input.gap_start as u16);
+        }
+    }
+}
+
+pub struct ThemePickerComponent {
+    themes: Vec<String>,
+    local_state: ListState,
+    // theme that was active when the picker was opened, restored on cancel.
+    original_theme: Option<String>,
+}
+
+impl Component for ThemePickerComponent {
+    fn render(&mut self, area: Rect, frame: &mut Frame, state: &AppState) {
+        if self.original_theme.is_none() {
+            self.original_theme = Some(state.theme.clone());
+            let index = self.themes.iter().position(|theme| *theme == state.theme);
+            self.local_state.select(index);
+        }
+        frame.render_widget(ratatui::widgets::Clear, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(state.ui_theme.border_style())
+            .title(Span::styled(" Themes ", state.ui_theme.title_style()))
+            .title_bottom(Span::styled(
+                format!(
+                    " [{}]: Apply | [{}]: Cancel ",
+                    state.keymap.label(&AppMode::ThemePicker, Action::Confirm),
+                    state.keymap.label(&AppMode::ThemePicker, Action::Back)
+                ),
+                state.ui_theme.help_style(),
+            ))
+            .title_alignment(ratatui::layout::Alignment::Center);
+        let items: Vec<ListItem> = self
+            .themes
+            .iter()
+            .map(|theme| ListItem::new(theme.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .style(state.ui_theme.text_style())
+            .highlight_style(state.ui_theme.highlight_style());
+        frame.render_stateful_widget(list, area, &mut self.local_state);
+    }
+
+    fn handle_action(&mut self, action: Action, state: &mut AppState) {
+        match action {
+            Action::SelectNext => self.local_state.select_next(),
+            Action::SelectPrevious => self.local_state.select_previous(),
+            Action::Confirm => {
+                self.original_theme = None;
+                state.mode = AppMode::Command;
+                return;
+            }
+            _ => {}
+        }
+        // preview the highlighted theme in the editor.
+        let index = self
+            .local_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.themes.len() - 1);
+        state.theme = self.themes[index].clone();
+    }
+}
+
+impl ThemePickerComponent {
+    fn new(themes: Vec<String>) -> Self {
+        ThemePickerComponent {
+            themes,
+            local_state: ListState::default(),
+            original_theme: None,
+        }
+    }
+
+    pub fn cancel(&mut self, state: &mut AppState) {
+        if let Some(theme) = self.original_theme.take() {
+            state.theme = theme;
         }
     }
 }
@@ -493,15 +1802,118 @@ pub struct ViewManager {
     pub editor_component: EditorComponent,
     pub search_component: SearchComponent,
     pub add_snippet_popup_component: AddSnippetPopupComponent,
+    pub theme_picker_component: ThemePickerComponent,
+}
+
+impl ViewManager {
+    /// Component receiving the input of `mode`, command mode is handled by the app itself.
+    pub fn active_component(&mut self, mode: AppMode) -> Option<&mut dyn Component> {
+        match mode {
+            AppMode::Command => None,
+            AppMode::Select => Some(&mut self.snippet_list_component),
+            AppMode::Edit => Some(&mut self.editor_component),
+            AppMode::Search => Some(&mut self.search_component),
+            AppMode::Popup => Some(&mut self.add_snippet_popup_component),
+            AppMode::ThemePicker => Some(&mut self.theme_picker_component),
+        }
+    }
 }
 
 impl ViewManager {
-    pub fn new() -> Self {
+    pub fn new(config: &EditorConfig, syntax_set: SyntaxSet, theme_set: ThemeSet) -> Self {
+        let themes = theme_set.themes.keys().cloned().collect();
         ViewManager {
             snippet_list_component: SnippetListComponent::new(),
-            editor_component: EditorComponent::new(),
+            editor_component: EditorComponent::new(config, syntax_set, theme_set),
+            theme_picker_component: ThemePickerComponent::new(themes),
             search_component: SearchComponent::new(),
             add_snippet_popup_component: AddSnippetPopupComponent::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{Keymap, KeysConfig};
+    use crate::theme::UiTheme;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn snippet(code: &str) -> Snippet {
+        Snippet {
+            language: "txt".to_string(),
+            code: code.to_string(),
+            title: code.to_string(),
+        }
+    }
+
+    fn open_editor(snippets: Vec<Snippet>) -> (EditorComponent, AppState) {
+        let keymap = Keymap::new(&KeysConfig::default()).unwrap();
+        let state = AppState::new(
+            snippets,
+            "base16-ocean.dark".to_string(),
+            UiTheme::default(),
+            keymap,
+        );
+        let mut editor = EditorComponent::new(
+            &EditorConfig::default(),
+            SyntaxSet::load_defaults_newlines(),
+            ThemeSet::load_defaults(),
+        );
+        render(&mut editor, &state);
+        (editor, state)
+    }
+
+    fn render(editor: &mut EditorComponent, state: &AppState) {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal
+            .draw(|frame| editor.render(frame.area(), frame, state))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_pane_views() {
+        let (mut editor, mut state) = open_editor(vec![snippet("one\ntwo\nthree")]);
+        let view = |editor: &EditorComponent| {
+            let open = &editor.buffers[&0];
+            (open.gap_buffer.cursor(), open.selection_anchor)
+        };
+        let start = view(&editor);
+        assert_eq!(start, (13, None));
+
+        // the cursor moves and selects in the new pane only
+        editor.handle_action(Action::SplitVertical, &mut state);
+        render(&mut editor, &state);
+        editor.handle_action(Action::CursorUp, &mut state);
+        editor.handle_action(Action::ExtendSelectionLeft, &mut state);
+        let moved = view(&editor);
+        assert_eq!(moved, (6, Some(7)));
+        editor.handle_action(Action::FocusLeft, &mut state);
+        assert_eq!(view(&editor), start);
+        editor.handle_action(Action::FocusRight, &mut state);
+        assert_eq!(view(&editor), moved);
+        // the text is still shared
+        assert_eq!(editor.buffers.len(), 1);
+    }
+
+    #[test]
+    fn test_track_edits() {
+        let (mut editor, mut state) = open_editor(vec![snippet("one"), snippet("two")]);
+        let key = Event::Key(KeyEvent::from(KeyCode::Char('s')));
+        editor.handle_event(&key, &mut state);
+        // the focused snippet is modified, its text is only copied when asked for
+        editor.track_edits(&mut state);
+        assert!(state.is_modified(0) && !state.is_modified(1));
+        assert_eq!(state.snippet(0).unwrap().code, "one");
+        editor.sync_buffer_to_state(&mut state);
+        assert_eq!(state.snippet(0).unwrap().code, "ones");
+
+        // and once the focus leaves it
+        editor.handle_event(&key, &mut state);
+        state.select_snippet(1);
+        render(&mut editor, &state);
+        editor.track_edits(&mut state);
+        assert_eq!(state.snippet(0).unwrap().code, "oness");
+        assert!(!state.is_modified(1));
+    }
+}
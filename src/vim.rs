@@ -0,0 +1,814 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::HashMap, fmt, ops::Range};
+
+use crate::config::EditorConfig;
+use crate::editor::{GapBuffer, column_index, display_width};
+use crate::indent::{self, IndentStyle};
+
+const UNNAMED_REGISTER: char = '"';
+const YANK_REGISTER: char = '0';
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl fmt::Display for VimMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VimMode::Normal => f.write_str("NORMAL"),
+            VimMode::Insert => f.write_str("INSERT"),
+            VimMode::Visual => f.write_str("VISUAL"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Motion {
+    Left,
+    Right,
+    Down,
+    Up,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    LineEnd,
+    FirstLine,
+    LastLine,
+}
+
+impl Motion {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(Motion::Left),
+            'l' => Some(Motion::Right),
+            'j' => Some(Motion::Down),
+            'k' => Some(Motion::Up),
+            'w' => Some(Motion::WordForward),
+            'b' => Some(Motion::WordBackward),
+            'e' => Some(Motion::WordEnd),
+            '0' => Some(Motion::LineStart),
+            '$' => Some(Motion::LineEnd),
+            'G' => Some(Motion::LastLine),
+            _ => None,
+        }
+    }
+
+    /// Linewise motions make operators act on whole lines.
+    fn is_linewise(self) -> bool {
+        matches!(
+            self,
+            Motion::Down | Motion::Up | Motion::FirstLine | Motion::LastLine
+        )
+    }
+
+    /// Inclusive motions make operators include the character under the target.
+    fn is_inclusive(self) -> bool {
+        matches!(self, Motion::WordEnd)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+/// Keys of a normal mode command that hasn't been completed yet.
+#[derive(Default)]
+struct Pending {
+    count: Option<usize>,
+    operator: Option<(Operator, usize)>,
+    register: Option<char>,
+    awaiting_register: bool,
+    g_prefix: bool,
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.count.is_none()
+            && self.operator.is_none()
+            && self.register.is_none()
+            && !self.awaiting_register
+            && !self.g_prefix
+    }
+}
+
+/// Vim emulation on top of a `GapBuffer`.
+pub struct Vim {
+    mode: VimMode,
+    pending: Pending,
+    registers: HashMap<char, Register>,
+    visual_anchor: usize,
+    // column kept by vertical motions across shorter lines.
+    desired_column: Option<usize>,
+    tab_size: usize,
+    auto_indent: bool,
+    auto_close: bool,
+    // keys of the change being typed, and of the last completed one for `.`.
+    recording: Vec<KeyEvent>,
+    last_change: Vec<KeyEvent>,
+    changed: bool,
+    replaying: bool,
+}
+
+impl Vim {
+    pub fn new(config: &EditorConfig) -> Self {
+        Vim {
+            mode: VimMode::Normal,
+            pending: Pending::default(),
+            registers: HashMap::new(),
+            visual_anchor: 0,
+            desired_column: None,
+            tab_size: config.tab_size,
+            auto_indent: config.auto_indent,
+            auto_close: config.auto_close,
+            recording: Vec::new(),
+            last_change: Vec::new(),
+            changed: false,
+            replaying: false,
+        }
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Whether the emulation is in normal mode with no command being typed.
+    pub fn is_idle(&self) -> bool {
+        self.mode == VimMode::Normal && self.pending.is_empty()
+    }
+
+    /// Characters covered by the visual selection.
+    pub fn selection(&self, buffer: &GapBuffer) -> Option<Range<usize>> {
+        if self.mode != VimMode::Visual || buffer.is_empty() {
+            return None;
+        }
+        let cursor = buffer.cursor();
+        let start = self.visual_anchor.min(cursor);
+        let end = (self.visual_anchor.max(cursor) + 1).min(buffer.len());
+        Some(start..end)
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent, buffer: &mut GapBuffer, style: IndentStyle) {
+        if !self.replaying {
+            self.recording.push(*key);
+        }
+        match self.mode {
+            VimMode::Insert => self.handle_insert_key(key, buffer, style),
+            VimMode::Normal | VimMode::Visual => self.handle_normal_key(key, buffer, style),
+        }
+        if self.replaying {
+            return;
+        }
+        // a change is complete once we are back in normal mode with nothing pending, and is
+        // undone as a whole.
+        if self.mode == VimMode::Normal && self.pending.is_empty() {
+            buffer.commit();
+            if self.changed {
+                self.last_change = std::mem::take(&mut self.recording);
+            } else {
+                self.recording.clear();
+            }
+            self.changed = false;
+        }
+    }
+
+    /// Typing is the same as in the editor, with its indentation and auto closing pairs.
+    fn handle_insert_key(&mut self, key: &KeyEvent, buffer: &mut GapBuffer, style: IndentStyle) {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = VimMode::Normal;
+                if buffer.cursor() > buffer.line_start(buffer.cursor()) {
+                    buffer.move_gap(buffer.cursor() - 1);
+                }
+            }
+            KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => {
+                if self.auto_close {
+                    indent::insert_char(buffer, c, style);
+                } else {
+                    buffer.insert_char(c);
+                }
+            }
+            KeyCode::Enter if self.auto_indent => indent::new_line(buffer, style),
+            KeyCode::Enter => buffer.insert_char('\n'),
+            KeyCode::Tab => buffer.insert_str(&style.unit()),
+            KeyCode::Backspace if self.auto_close => indent::delete_backward(buffer),
+            KeyCode::Backspace => buffer.delete_backward(),
+            KeyCode::Left => buffer.move_gap(buffer.previous_boundary(buffer.cursor())),
+            KeyCode::Right => buffer.move_gap(buffer.next_boundary(buffer.cursor())),
+            _ => return,
+        }
+        self.changed = true;
+    }
+
+    fn handle_normal_key(&mut self, key: &KeyEvent, buffer: &mut GapBuffer, style: IndentStyle) {
+        let c = match key.code {
+            KeyCode::Char(c) if (key.modifiers - KeyModifiers::SHIFT).is_empty() => c,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Down => 'j',
+            KeyCode::Up => 'k',
+            KeyCode::Esc => {
+                self.pending = Pending::default();
+                self.mode = VimMode::Normal;
+                self.clamp_cursor(buffer);
+                return;
+            }
+            KeyCode::Char('r')
+                if key.modifiers == KeyModifiers::CONTROL && self.mode == VimMode::Normal =>
+            {
+                let count = self.pending.count.unwrap_or(1);
+                self.pending = Pending::default();
+                for _ in 0..count {
+                    if !buffer.redo() {
+                        break;
+                    }
+                }
+                self.clamp_cursor(buffer);
+                return;
+            }
+            _ => return,
+        };
+
+        if self.pending.awaiting_register {
+            self.pending.awaiting_register = false;
+            self.pending.register = Some(c);
+            return;
+        }
+        if let Some(digit) = c.to_digit(10)
+            && (digit != 0 || self.pending.count.is_some())
+        {
+            // no motion or change repeats more often than there are characters, or lines
+            let count = self.pending.count.unwrap_or(0);
+            let count = count.saturating_mul(10).saturating_add(digit as usize);
+            self.pending.count = Some(count.min(buffer.len() + 1));
+            return;
+        }
+        if self.pending.g_prefix {
+            self.pending.g_prefix = false;
+            if c == 'g' {
+                self.apply_motion(Motion::FirstLine, buffer);
+            } else {
+                self.pending = Pending::default();
+            }
+            return;
+        }
+
+        if let Some(motion) = Motion::from_char(c) {
+            self.apply_motion(motion, buffer);
+            return;
+        }
+        if let Some(operator) = Operator::from_char(c) {
+            self.apply_operator_key(operator, buffer);
+            return;
+        }
+
+        let count = self.pending.count.unwrap_or(1);
+        match (self.mode, c) {
+            (_, '"') => self.pending.awaiting_register = true,
+            (_, 'g') => self.pending.g_prefix = true,
+            (VimMode::Visual, 'v') => self.mode = VimMode::Normal,
+            (VimMode::Visual, 'x') => self.apply_operator_key(Operator::Delete, buffer),
+            (VimMode::Normal, 'v') => {
+                self.mode = VimMode::Visual;
+                self.visual_anchor = buffer.cursor();
+            }
+            (VimMode::Normal, 'x') => {
+                let cursor = buffer.cursor();
+                let end = repeat(cursor, count, |index| buffer.next_boundary(index))
+                    .min(buffer.line_end(cursor));
+                if end > cursor {
+                    self.operate(Operator::Delete, cursor..end, false, buffer);
+                }
+            }
+            (VimMode::Normal, 'u') => {
+                for _ in 0..count {
+                    if !buffer.undo() {
+                        break;
+                    }
+                }
+            }
+            (VimMode::Normal, 'p') => self.put(true, count, buffer),
+            (VimMode::Normal, 'P') => self.put(false, count, buffer),
+            (VimMode::Normal, 'i') => self.enter_insert(),
+            (VimMode::Normal, 'a') => {
+                if buffer.cursor() < buffer.line_end(buffer.cursor()) {
+                    buffer.move_gap(buffer.next_boundary(buffer.cursor()));
+                }
+                self.enter_insert();
+            }
+            (VimMode::Normal, 'I') => {
+                buffer.move_gap(first_non_blank(buffer, buffer.cursor()));
+                self.enter_insert();
+            }
+            (VimMode::Normal, 'A') => {
+                buffer.move_gap(buffer.line_end(buffer.cursor()));
+                self.enter_insert();
+            }
+            (VimMode::Normal, 'o') => {
+                buffer.move_gap(buffer.line_end(buffer.cursor()));
+                buffer.insert_char('\n');
+                self.enter_insert();
+            }
+            (VimMode::Normal, 'O') => {
+                buffer.move_gap(buffer.line_start(buffer.cursor()));
+                buffer.insert_char('\n');
+                buffer.move_gap(buffer.cursor() - 1);
+                self.enter_insert();
+            }
+            (VimMode::Normal, '.') => {
+                self.pending = Pending::default();
+                self.repeat_last_change(count, buffer, style);
+                return;
+            }
+            _ => {}
+        }
+        if !self.pending.awaiting_register && !self.pending.g_prefix {
+            self.pending = Pending::default();
+        }
+        self.clamp_cursor(buffer);
+    }
+
+    fn enter_insert(&mut self) {
+        self.mode = VimMode::Insert;
+        self.changed = true;
+    }
+
+    fn apply_operator_key(&mut self, operator: Operator, buffer: &mut GapBuffer) {
+        if let Some(range) = self.selection(buffer) {
+            self.mode = VimMode::Normal;
+            self.operate(operator, range, false, buffer);
+            self.pending = Pending::default();
+            self.clamp_cursor(buffer);
+            return;
+        }
+        let count = self.pending.count.take().unwrap_or(1);
+        match self.pending.operator {
+            // a doubled operator such as `dd` acts on whole lines.
+            Some((pending, operator_count)) if pending == operator => {
+                let cursor = buffer.cursor();
+                let last_line = nth_line_start(
+                    buffer,
+                    cursor,
+                    operator_count.saturating_mul(count).saturating_sub(1),
+                );
+                self.operate(operator, cursor..last_line, true, buffer);
+                self.pending = Pending::default();
+                self.clamp_cursor(buffer);
+            }
+            Some(_) => self.pending = Pending::default(),
+            None => self.pending.operator = Some((operator, count)),
+        }
+    }
+
+    fn apply_motion(&mut self, motion: Motion, buffer: &mut GapBuffer) {
+        let count = self.pending.count.take();
+        let operator = self.pending.operator.take();
+        let total = count
+            .unwrap_or(1)
+            .saturating_mul(operator.map_or(1, |(_, count)| count));
+        let cursor = buffer.cursor();
+        let mut target = self.motion_target(motion, count, total, operator.is_some(), buffer);
+
+        let Some((operator, _)) = operator else {
+            buffer.move_gap(target);
+            self.pending = Pending::default();
+            self.clamp_cursor(buffer);
+            return;
+        };
+        // like vim, `cw` changes up to the end of the word.
+        let mut inclusive = motion.is_inclusive();
+        if operator == Operator::Change && motion == Motion::WordForward {
+            let on_word = buffer.char_at(cursor).is_some_and(|c| !c.is_whitespace());
+            if on_word {
+                target = word_end(buffer, cursor, total);
+                inclusive = true;
+            }
+        }
+        let (start, end) = (cursor.min(target), cursor.max(target));
+        let end = if inclusive {
+            (end + 1).min(buffer.len())
+        } else {
+            end
+        };
+        self.operate(operator, start..end, motion.is_linewise(), buffer);
+        self.pending = Pending::default();
+        self.clamp_cursor(buffer);
+    }
+
+    fn motion_target(
+        &mut self,
+        motion: Motion,
+        count: Option<usize>,
+        total: usize,
+        is_operator_pending: bool,
+        buffer: &GapBuffer,
+    ) -> usize {
+        let cursor = buffer.cursor();
+        let line_start = buffer.line_start(cursor);
+        let line_end = buffer.line_end(cursor);
+        if !matches!(motion, Motion::Down | Motion::Up) {
+            self.desired_column = None;
+        }
+        match motion {
+            Motion::Left => {
+                repeat(cursor, total, |index| buffer.previous_boundary(index)).max(line_start)
+            }
+            Motion::Right => {
+                // an operator may reach the end of the line, the cursor may not.
+                let last = if is_operator_pending {
+                    line_end
+                } else {
+                    buffer.previous_boundary(line_end).max(line_start)
+                };
+                repeat(cursor, total, |index| buffer.next_boundary(index)).min(last)
+            }
+            Motion::Down | Motion::Up => {
+                let column = *self.desired_column.get_or_insert_with(|| {
+                    display_width(&buffer.slice(line_start..cursor), self.tab_size)
+                });
+                let target_line = if motion == Motion::Down {
+                    nth_line_start(buffer, cursor, total)
+                } else {
+                    repeat(line_start, total, |start| {
+                        buffer.line_start(start.saturating_sub(1))
+                    })
+                };
+                let line = buffer.slice(target_line..buffer.line_end(target_line));
+                target_line + column_index(&line, column, self.tab_size)
+            }
+            Motion::WordForward => repeat(cursor, total, |index| next_word_start(buffer, index)),
+            Motion::WordBackward => {
+                repeat(cursor, total, |index| previous_word_start(buffer, index))
+            }
+            Motion::WordEnd => word_end(buffer, cursor, total),
+            Motion::LineStart => line_start,
+            Motion::LineEnd => line_end,
+            Motion::FirstLine | Motion::LastLine => {
+                let line = match (motion, count) {
+                    (_, Some(line)) => line.saturating_sub(1),
+                    (Motion::FirstLine, None) => 0,
+                    _ => usize::MAX,
+                };
+                let start = nth_line_start(buffer, 0, line);
+                first_non_blank(buffer, start)
+            }
+        }
+    }
+
+    fn operate(
+        &mut self,
+        operator: Operator,
+        range: Range<usize>,
+        linewise: bool,
+        buffer: &mut GapBuffer,
+    ) {
+        let mut range = range;
+        if linewise {
+            range.start = buffer.line_start(range.start);
+            range.end = buffer.line_end(range.end.max(range.start));
+            if operator != Operator::Change {
+                if range.end < buffer.len() {
+                    // take the newline ending the last line.
+                    range.end += 1;
+                } else if range.start > 0 {
+                    // on the last line, take the newline before it instead.
+                    range.start -= 1;
+                }
+            }
+        }
+        let mut text = buffer.slice(range.clone());
+        if linewise && !text.ends_with('\n') {
+            text = text.trim_start_matches('\n').to_string();
+            text.push('\n');
+        }
+        self.store_register(operator, Register { text, linewise });
+        match operator {
+            Operator::Yank => buffer.move_gap(range.start),
+            Operator::Delete => {
+                buffer.delete_range(range.clone());
+                if linewise {
+                    let start = buffer.line_start(range.start.min(buffer.len()));
+                    buffer.move_gap(first_non_blank(buffer, start));
+                }
+                self.changed = true;
+            }
+            Operator::Change => {
+                buffer.delete_range(range);
+                self.enter_insert();
+            }
+        }
+    }
+
+    fn store_register(&mut self, operator: Operator, register: Register) {
+        match self.pending.register.take() {
+            Some(name) => {
+                self.registers.insert(name, register.clone());
+            }
+            None if operator == Operator::Yank => {
+                self.registers.insert(YANK_REGISTER, register.clone());
+            }
+            None => {}
+        }
+        self.registers.insert(UNNAMED_REGISTER, register);
+    }
+
+    fn put(&mut self, after: bool, count: usize, buffer: &mut GapBuffer) {
+        let name = self.pending.register.take().unwrap_or(UNNAMED_REGISTER);
+        let Some(register) = self.registers.get(&name).cloned() else {
+            return;
+        };
+        let text = register.text.repeat(count);
+        let cursor = buffer.cursor();
+        if register.linewise {
+            let position = if after {
+                let line_end = buffer.line_end(cursor);
+                if line_end == buffer.len() {
+                    buffer.move_gap(line_end);
+                    buffer.insert_char('\n');
+                    buffer.len()
+                } else {
+                    line_end + 1
+                }
+            } else {
+                buffer.line_start(cursor)
+            };
+            buffer.move_gap(position);
+            let text = if position == buffer.len() {
+                text.trim_end_matches('\n').to_string()
+            } else {
+                text
+            };
+            buffer.insert_str(&text);
+            buffer.move_gap(first_non_blank(buffer, position));
+        } else {
+            let position = if after && cursor < buffer.line_end(cursor) {
+                cursor + 1
+            } else {
+                cursor
+            };
+            buffer.move_gap(position);
+            buffer.insert_str(&text);
+            buffer.move_gap(buffer.cursor().saturating_sub(1).max(position));
+        }
+        self.changed = true;
+    }
+
+    fn repeat_last_change(&mut self, count: usize, buffer: &mut GapBuffer, style: IndentStyle) {
+        let keys = self.last_change.clone();
+        self.recording.clear();
+        self.replaying = true;
+        for _ in 0..count {
+            for key in &keys {
+                self.handle_key(key, buffer, style);
+            }
+        }
+        self.replaying = false;
+        self.changed = false;
+    }
+
+    /// In normal mode the cursor sits on a character, never past the end of the line.
+    fn clamp_cursor(&self, buffer: &mut GapBuffer) {
+        if self.mode == VimMode::Insert {
+            return;
+        }
+        let cursor = buffer.cursor();
+        let line_start = buffer.line_start(cursor);
+        if cursor > line_start && cursor == buffer.line_end(cursor) {
+            buffer.move_gap(buffer.previous_boundary(cursor));
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Whitespace
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+fn next_word_start(buffer: &GapBuffer, index: usize) -> usize {
+    let len = buffer.len();
+    let mut index = index;
+    if let Some(class) = buffer.char_at(index).map(char_class) {
+        while index < len && buffer.char_at(index).map(char_class) == Some(class) {
+            index += 1;
+        }
+    }
+    while index < len && buffer.char_at(index).is_some_and(char::is_whitespace) {
+        // an empty line counts as a word.
+        if buffer.char_at(index) == Some('\n') && buffer.char_at(index + 1) == Some('\n') {
+            return index + 1;
+        }
+        index += 1;
+    }
+    index
+}
+
+fn previous_word_start(buffer: &GapBuffer, index: usize) -> usize {
+    let mut index = index;
+    while index > 0 && buffer.char_at(index - 1).is_some_and(char::is_whitespace) {
+        index -= 1;
+    }
+    let Some(class) = index
+        .checked_sub(1)
+        .and_then(|i| buffer.char_at(i))
+        .map(char_class)
+    else {
+        return index;
+    };
+    while index > 0 && buffer.char_at(index - 1).map(char_class) == Some(class) {
+        index -= 1;
+    }
+    index
+}
+
+/// Applies `step` to `index` `count` times, stopping once it no longer moves.
+fn repeat(index: usize, count: usize, step: impl Fn(usize) -> usize) -> usize {
+    let mut index = index;
+    for _ in 0..count {
+        let next = step(index);
+        if next == index {
+            break;
+        }
+        index = next;
+    }
+    index
+}
+
+fn word_end(buffer: &GapBuffer, index: usize, count: usize) -> usize {
+    let len = buffer.len();
+    let mut index = index;
+    for _ in 0..count {
+        if index + 1 >= len {
+            break;
+        }
+        index += 1;
+        while index < len && buffer.char_at(index).is_some_and(char::is_whitespace) {
+            index += 1;
+        }
+        let class = buffer.char_at(index).map(char_class);
+        while index + 1 < len && buffer.char_at(index + 1).map(char_class) == class {
+            index += 1;
+        }
+    }
+    index.min(len.saturating_sub(1))
+}
+
+/// Start of the line `count` lines below the one containing `index`, or of the last line.
+fn nth_line_start(buffer: &GapBuffer, index: usize, count: usize) -> usize {
+    let mut start = buffer.line_start(index);
+    for _ in 0..count {
+        let end = buffer.line_end(start);
+        if end >= buffer.len() {
+            break;
+        }
+        start = end + 1;
+    }
+    start
+}
+
+fn first_non_blank(buffer: &GapBuffer, index: usize) -> usize {
+    let start = buffer.line_start(index);
+    let end = buffer.line_end(index);
+    (start..end)
+        .find(|&i| buffer.char_at(i).is_some_and(|c| c != ' ' && c != '\t'))
+        .unwrap_or(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STYLE: IndentStyle = IndentStyle {
+        use_tabs: false,
+        width: 4,
+    };
+
+    fn type_keys(vim: &mut Vim, buffer: &mut GapBuffer, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\u{1b}' => KeyCode::Esc,
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                '\u{8}' => KeyCode::Backspace,
+                c => KeyCode::Char(c),
+            };
+            vim.handle_key(&KeyEvent::new(code, KeyModifiers::NONE), buffer, STYLE);
+        }
+    }
+
+    fn run(text: &str, keys: &str) -> (String, usize) {
+        let mut buffer = GapBuffer::from_str(text, 8);
+        buffer.move_gap(0);
+        let mut vim = Vim::new(&EditorConfig::default());
+        type_keys(&mut vim, &mut buffer, keys);
+        (buffer.to_string(), buffer.cursor())
+    }
+
+    #[test]
+    fn test_motions() {
+        assert_eq!(run("one two three", "w").1, 4);
+        assert_eq!(run("one two three", "2w").1, 8);
+        assert_eq!(run("one two three", "$b").1, 8);
+        assert_eq!(run("one two three", "e").1, 2);
+        assert_eq!(run("one two three", "$").1, 12);
+        assert_eq!(run("ab\ncd\nef", "G").1, 6);
+        assert_eq!(run("ab\ncd\nef", "Ggg").1, 0);
+        assert_eq!(run("ab\ncd\nef", "lj").1, 4);
+        assert_eq!(run("abc\nd\nefg", "llj").1, 4);
+        assert_eq!(run("abc\nd\nefg", "lljj").1, 8);
+        assert_eq!(run("\tx\nabcdefg", "lj").1, 7);
+        // huge counts neither overflow nor loop for long
+        assert_eq!(run("one two three", "99999999999999999999999l").1, 12);
+        assert_eq!(run("ab\ncd\nef", "99999999999999999999999jk").1, 3);
+        assert_eq!(
+            run("a\nb\nc", "99999999999999999999999d99999999999999999999d").0,
+            ""
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(run("one two three", "dw").0, "two three");
+        assert_eq!(run("one two three", "d2w").0, "three");
+        assert_eq!(run("one two three", "2dw").0, "three");
+        assert_eq!(run("one two three", "cwsix\u{1b}").0, "six two three");
+        assert_eq!(run("one two three", "wd$").0, "one ");
+        assert_eq!(run("a\nb\nc", "jdd").0, "a\nc");
+        assert_eq!(run("a\nb\nc", "2dd").0, "c");
+        assert_eq!(run("a\nb\nc", "Gdd").0, "a\nb");
+        assert_eq!(run("a\nb\nc", "dj").0, "c");
+        assert_eq!(run("a\nb\nc", "yyp").0, "a\na\nb\nc");
+        assert_eq!(run("one two", "ywP").0, "one one two");
+        assert_eq!(run("abc", "xp").0, "bac");
+    }
+
+    #[test]
+    fn test_insert() {
+        assert_eq!(run("", "i{\nfoo\u{1b}").0, "{\n    foo\n}");
+        assert_eq!(run("", "i\tx").0, "    x");
+        assert_eq!(run("", "i(\u{8}").0, "");
+        assert_eq!(run("", "i[a]\u{1b}").0, "[a]");
+    }
+
+    #[test]
+    fn test_registers_and_repeat() {
+        assert_eq!(run("one two", "\"ayww\"ap").0, "one tone wo");
+        assert_eq!(run("a b c d", "x..").0, " c d");
+        assert_eq!(run("a\nb\nc", "dd.").0, "c");
+        assert_eq!(run("x", "ia\u{1b}.").0, "aax");
+    }
+
+    #[test]
+    fn test_undo() {
+        assert_eq!(run("one two three", "dwdwu").0, "two three");
+        assert_eq!(run("one two three", "cwsix\u{1b}u").0, "one two three");
+        assert_eq!(run("a\nb\nc", "dd.2u").0, "a\nb\nc");
+        let mut buffer = GapBuffer::from_str("abc", 8);
+        buffer.move_gap(0);
+        let mut vim = Vim::new(&EditorConfig::default());
+        type_keys(&mut vim, &mut buffer, "xxuu");
+        let redo = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        vim.handle_key(&redo, &mut buffer, STYLE);
+        assert_eq!(buffer.to_string(), "bc");
+    }
+
+    #[test]
+    fn test_visual() {
+        assert_eq!(run("one two three", "vlld").0, " two three");
+        assert_eq!(run("one two three", "wvey$p").0, "one two threetwo");
+    }
+}